@@ -23,6 +23,7 @@ async fn iterate_albums<Fut>(
     api_secret: &str,
     access_token: &str,
     access_token_secret: &str,
+    dry_run: bool,
     album_op: impl Fn(Album) -> Fut,
 ) -> Result<()>
 where
@@ -31,12 +32,16 @@ where
     // The API key/secret is obtained from your SmugMug account
     // The API key is the only required field for accessing public accounts
     // The Access Token/Secret is obtained via the OAuth1 authentication process
-    let client = Client::new(Creds::from_tokens(
-        api_key,
-        Some(api_secret),
-        Some(access_token),
-        Some(access_token_secret),
-    ));
+    // In dry run mode the updates are logged instead of sent
+    let client = Client::builder()
+        .creds(Creds::from_tokens(
+            api_key,
+            Some(api_secret),
+            Some(access_token),
+            Some(access_token_secret),
+        ))
+        .dry_run(dry_run)
+        .build()?;
 
     // Get information for the authenticated user
     let user_info = User::authenticated_user_info(client.clone()).await?;
@@ -90,6 +95,9 @@ async fn main() -> Result<()> {
     let token_cache = std::env::var("SMUGMUG_AUTH_CACHE")?;
     let tokens = get_smugmug_tokens(token_cache.into())?;
 
+    // Pass --dry-run to see which upload keys would be removed without removing them
+    let dry_run = std::env::args().any(|v| v == "--dry-run");
+
     // Date to cutoff no matter what just in case some spam/maliciousness is happening
     let cutoff_from_date_created_dt = Utc::now() - Duration::days(60);
     // Date we use to give leeway from last change time.
@@ -133,6 +141,7 @@ async fn main() -> Result<()> {
         &api_secret,
         &tokens.token,
         &tokens.secret,
+        dry_run,
         cleaner,
     )
     .await?;
@@ -23,13 +23,33 @@
 //!     - Can list the images contained in an Album.
 //! - Retrieve Image information.
 //!     - Download of archive image supported.
+//!     - Streaming download of the archive to a writer or file with MD5 verification data.
+//!     - Resumable download of the archive using range requests, verified against the archived size and MD5.
+//!     - Cancellation of child listings and archive downloads via [`v2::CancellationToken`], returning the partial results and a position to resume from.
 //! - Lower level interface for handling more direct communication.
 //!     - Exposes the SmugMug API Rate Limit information.
+//!     - Opt-in retry with backoff for 429 and 5xx responses via [`v2::RetryPolicy`].
+//!     - Opt-in throttling that spaces out or pauses requests as the rate limit runs low via [`v2::ThrottlePolicy`].
+//!     - Configurable API origin, timeouts, user agent and http client via [`v2::ClientBuilder`].
+//!     - Optional conditional GET caching with ETag/Last-Modified via [`v2::HttpCache`].
+//!     - Optional in-memory LRU object cache with per-type TTLs via [`v2::ObjectCache`].
+//!     - On-disk response cache via [`v2::DiskHttpCache`] with an offline mode serving only cached responses.
+//!     - Record and replay of traffic to a cassette file with credentials redacted via [`v2::CassetteTransport`].
+//!     - Substitutable API implementation for tests via [`v2::SmugMugApi`] and [`v2::Client::from_api`].
+//!     - Request metrics with a Prometheus text renderer via [`v2::Client::metrics`].
+//!     - Separate limits on JSON requests and binary transfers in flight via [`v2::ClientBuilder`].
+//!     - Dry-run mode logging PATCH/POST/PUT/DELETE requests instead of sending them via [`v2::ClientBuilder::dry_run`].
+//!     - Journal of mutations with the object state before and after that can be undone via [`v2::MutationJournal`].
+//!     - Request budgets failing calls fast once a limit is used up via [`v2::Client::with_budget`].
+//!     - Registry of named accounts each with their own client, running an operation across all of them via [`v2::AccountRegistry`].
+//! - Synchronous API via `v2::blocking` when the `blocking` feature is enabled.
+//! - In-process mock SmugMug API server via `v2::mock` when the `mock` feature is enabled.
+//! - Structured spans per API call with credentials redacted when the `tracing` feature is enabled.
 //!
 //! *The SmugMug API uses OAuth1. This library handles the request signing.
 //! Getting the Access Token/Secret is left up to the consumer of this library*
 //!
-//! *The [`v2::Client`] currently provides direct GET/PATCH/POST functionality to allow library usage
+//! *The [`v2::Client`] currently provides direct GET/PATCH/POST/PUT/DELETE functionality to allow library usage
 //! for features that may not be implemented yet in the higher level interfaces*
 //!
 //! ## Usage
@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::client::{Client, Creds};
+use crate::v2::errors::SmugMugError;
+use std::collections::BTreeMap;
+use std::future::Future;
+
+/// Named SmugMug accounts each with their own [`Client`].
+///
+/// Every account added from [`Creds`] gets a separately built client, so each tracks its own
+/// rate limit window.  [`AccountRegistry::run_all`] runs the same operation against every
+/// account at once and collects the results by account name.
+///
+/// ```rust,no_run
+/// use smugmug::v2::{AccountRegistry, Creds, User};
+///
+/// # async fn run() -> Result<(), smugmug::v2::SmugMugError> {
+/// let mut accounts = AccountRegistry::new();
+/// accounts.add("north", Creds::from_tokens("key", Some("secret"), Some("t1"), Some("s1")))?;
+/// accounts.add("south", Creds::from_tokens("key", Some("secret"), Some("t2"), Some("s2")))?;
+///
+/// let users = accounts.run_all(User::authenticated_user_info).await;
+/// for (name, user) in users.successes() {
+///     println!("{name}: {}", user.name);
+/// }
+/// for (name, err) in users.errors() {
+///     eprintln!("{name} failed: {err}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct AccountRegistry {
+    accounts: BTreeMap<String, Client>,
+}
+
+impl AccountRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an account using a client built from the credentials, replacing any account with
+    /// the same name
+    pub fn add(&mut self, name: &str, creds: Creds) -> Result<&Client, SmugMugError> {
+        let client = Client::builder().creds(creds).build()?;
+        self.add_client(name, client);
+        Ok(&self.accounts[name])
+    }
+
+    /// Adds an account using the given client, such as one configured with
+    /// [`Client::builder`].  Returns the client previously registered under the name.
+    pub fn add_client(&mut self, name: &str, client: Client) -> Option<Client> {
+        self.accounts.insert(name.to_string(), client)
+    }
+
+    /// Removes the account returning its client
+    pub fn remove(&mut self, name: &str) -> Option<Client> {
+        self.accounts.remove(name)
+    }
+
+    /// Client for the named account
+    pub fn client(&self, name: &str) -> Option<&Client> {
+        self.accounts.get(name)
+    }
+
+    /// Names of the registered accounts in sorted order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.accounts.keys().map(|v| v.as_str())
+    }
+
+    /// Number of registered accounts
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Whether there are no registered accounts
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Runs the operation with each account's client concurrently.  A failure for one account
+    /// doesn't stop the others and is reported in the results under its name.
+    pub async fn run_all<T, F, Fut>(&self, op: F) -> AccountResults<T>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T, SmugMugError>>,
+    {
+        let runs = self.accounts.iter().map(|(name, client)| {
+            let fut = op(client.clone());
+            async move { (name.clone(), fut.await) }
+        });
+        AccountResults {
+            results: futures::future::join_all(runs).await.into_iter().collect(),
+        }
+    }
+}
+
+/// Results of running an operation across the accounts of an [`AccountRegistry`]
+#[derive(Debug)]
+pub struct AccountResults<T> {
+    /// Result for each account by name
+    pub results: BTreeMap<String, Result<T, SmugMugError>>,
+}
+
+impl<T> AccountResults<T> {
+    /// Result for the named account
+    pub fn get(&self, name: &str) -> Option<&Result<T, SmugMugError>> {
+        self.results.get(name)
+    }
+
+    /// Accounts where the operation succeeded along with its value
+    pub fn successes(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.results
+            .iter()
+            .filter_map(|(name, result)| result.as_ref().ok().map(|v| (name.as_str(), v)))
+    }
+
+    /// Accounts where the operation failed along with the error
+    pub fn errors(&self) -> impl Iterator<Item = (&str, &SmugMugError)> {
+        self.results
+            .iter()
+            .filter_map(|(name, result)| result.as_ref().err().map(|v| (name.as_str(), v)))
+    }
+
+    /// Whether the operation succeeded for every account
+    pub fn all_succeeded(&self) -> bool {
+        self.results.values().all(|v| v.is_ok())
+    }
+}
@@ -5,13 +5,16 @@
  *  - MIT license <http://opensource.org/licenses/MIT>
  *  at your option.
  */
+use crate::v2::cancel::PositionTracker;
 use crate::v2::errors::SmugMugError;
 use crate::v2::macros::{
     obj_from_url, obj_update_from_uri, obj_update_from_url, objs_from_id_slice,
     stream_children_from_url,
 };
 use crate::v2::parsers::{from_privacy, is_none_or_empty_str};
-use crate::v2::{Client, Image, Pages, PrivacyLevel, API_ORIGIN};
+use crate::v2::{
+    CancellableStream, CancellationToken, Client, Image, PagePosition, Pages, PrivacyLevel,
+};
 use async_stream::try_stream;
 use chrono::{DateTime, Utc};
 use futures::Stream;
@@ -96,6 +99,23 @@ pub struct Album {
 impl Album {
     const BASE_URI: &'static str = "/api/v2/album/";
 
+    // Album that would have been created from the props when in dry run mode
+    pub(crate) fn from_dry_run_props(client: Client, album_props: CreateAlbumProps) -> Self {
+        Self {
+            client: Some(client),
+            url_name: album_props
+                .url_name
+                .unwrap_or_else(|| album_props.name.clone()),
+            web_uri: album_props.web_uri.unwrap_or_default(),
+            name: album_props.name,
+            description: album_props.description,
+            password_hint: album_props.password_hint,
+            upload_key: album_props.upload_key,
+            privacy: album_props.privacy,
+            ..Default::default()
+        }
+    }
+
     /// Returns information for the album at the provided full url
     pub async fn from_url(client: Client, url: &str) -> Result<Self, SmugMugError> {
         obj_from_url!(client, url, AlbumResponse, album)
@@ -103,9 +123,7 @@ impl Album {
 
     /// Returns information for the specified album id using the provided client
     pub async fn from_id(client: Client, id: &str) -> Result<Self, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join(Self::BASE_URI)?
-            .join(id)?;
+        let req_url = client.api_url(Self::BASE_URI)?.join(id)?;
         Self::from_url(client, req_url.as_str()).await
     }
 
@@ -118,7 +136,7 @@ impl Album {
     }
 
     /// Retrieves information about the images associated with this Album
-    pub fn images(&self) -> Result<impl Stream<Item=Result<Image, SmugMugError>>, SmugMugError> {
+    pub fn images(&self) -> Result<impl Stream<Item = Result<Image, SmugMugError>>, SmugMugError> {
         self.images_with_client(
             self.client
                 .as_ref()
@@ -131,7 +149,7 @@ impl Album {
     pub fn images_with_client(
         &self,
         client: Client,
-    ) -> Result<impl Stream<Item=Result<Image, SmugMugError>>, SmugMugError> {
+    ) -> Result<impl Stream<Item = Result<Image, SmugMugError>>, SmugMugError> {
         // Build up the query parameters
         let params: Vec<(&str, &str)> = Vec::new();
 
@@ -144,6 +162,49 @@ impl Album {
         ))
     }
 
+    /// Retrieves information about the images associated with this Album, stopping once the
+    /// token is cancelled.  The images returned before then are kept and the stream's
+    /// [`CancellableStream::resume_position`] can be passed as `resume_from` to continue the
+    /// listing later.
+    pub fn images_cancellable(
+        &self,
+        cancel: CancellationToken,
+        resume_from: Option<PagePosition>,
+    ) -> Result<CancellableStream<'_, Image>, SmugMugError> {
+        self.images_cancellable_with_client(
+            self.client
+                .as_ref()
+                .ok_or(SmugMugError::ClientNotFound())?
+                .clone(),
+            cancel,
+            resume_from,
+        )
+    }
+
+    /// Retrieves information about images associated with this Album using the provided client,
+    /// stopping once the token is cancelled
+    pub fn images_cancellable_with_client(
+        &self,
+        client: Client,
+        cancel: CancellationToken,
+        resume_from: Option<PagePosition>,
+    ) -> Result<CancellableStream<'_, Image>, SmugMugError> {
+        let params: Vec<(&str, &str)> = Vec::new();
+
+        let position = PositionTracker::default();
+        let stream = stream_children_from_url!(
+            client,
+            self.uris.album_images.as_ref(),
+            &params,
+            AlbumImagesResponse,
+            images,
+            resume_from,
+            Some(cancel),
+            position.clone()
+        );
+        Ok(CancellableStream::new(stream, position))
+    }
+
     /// Updates this Album's data fields
     pub async fn update_album_data_with_client(
         &self,
@@ -169,9 +230,7 @@ impl Album {
         data: Vec<u8>,
         id: &str,
     ) -> Result<Album, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join(Self::BASE_URI)?
-            .join(id)?;
+        let req_url = client.api_url(Self::BASE_URI)?.join(id)?;
         obj_update_from_url!(client, req_url.as_str(), data, AlbumResponse, album)
     }
 
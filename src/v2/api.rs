@@ -0,0 +1,131 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::client::{ApiParams, Client, Method, Response};
+use crate::v2::download::RangedBody;
+use crate::v2::errors::SmugMugError;
+use crate::v2::transport::HttpBodyStream;
+use futures::future::BoxFuture;
+use serde_json::Value;
+
+/// Operations against the SmugMug API that the [`User`](crate::v2::User),
+/// [`Node`](crate::v2::Node), [`Album`](crate::v2::Album) and [`Image`](crate::v2::Image)
+/// methods are built on.
+///
+/// [`Client`] implements this by sending the requests over HTTP.  Another implementation can be
+/// wrapped with [`Client::from_api`] so that code working with those objects can be tested
+/// against a fake returning canned objects.  JSON payloads are exchanged as
+/// [`serde_json::Value`] holding the `Response` member of the SmugMug reply, which the client
+/// converts to the requested type.
+///
+/// ```rust
+/// use futures::future::BoxFuture;
+/// use serde_json::{Value, json};
+/// use smugmug::v2::{
+///     ApiParams, Client, HttpBodyStream, Method, Response, SmugMugApi, SmugMugError, User,
+/// };
+/// use std::sync::Arc;
+///
+/// // Responds to every JSON request with the same user
+/// struct FakeApi;
+///
+/// impl SmugMugApi for FakeApi {
+///     fn request<'a>(
+///         &'a self,
+///         _method: Method,
+///         _url: &'a str,
+///         _data: Option<Vec<u8>>,
+///         _params: Option<&'a ApiParams<'a>>,
+///     ) -> BoxFuture<'a, Result<Response<Value>, SmugMugError>> {
+///         Box::pin(async {
+///             Ok(Response {
+///                 payload: Some(json!({"User": {"Name": "fake", "NickName": "fake"}})),
+///                 rate_limit: None,
+///             })
+///         })
+///     }
+///
+///     fn get_binary_stream<'a>(
+///         &'a self,
+///         url: &'a str,
+///         _params: Option<&'a ApiParams<'a>>,
+///     ) -> BoxFuture<'a, Result<Response<HttpBodyStream>, SmugMugError>> {
+///         Box::pin(async move { Err(SmugMugError::HttpStatus(404, url.to_string())) })
+///     }
+/// }
+///
+/// # async fn run() -> Result<(), SmugMugError> {
+/// let client = Client::from_api(Arc::new(FakeApi));
+/// let user = User::from_id(client, "fake").await?;
+/// assert_eq!(user.name, "fake");
+/// # Ok(())
+/// # }
+/// ```
+pub trait SmugMugApi: Send + Sync {
+    /// Performs a request returning the JSON `Response` member of the reply
+    fn request<'a>(
+        &'a self,
+        method: Method,
+        url: &'a str,
+        data: Option<Vec<u8>>,
+        params: Option<&'a ApiParams<'a>>,
+    ) -> BoxFuture<'a, Result<Response<Value>, SmugMugError>>;
+
+    /// Performs a GET request for binary data returning the body as a stream
+    fn get_binary_stream<'a>(
+        &'a self,
+        url: &'a str,
+        params: Option<&'a ApiParams<'a>>,
+    ) -> BoxFuture<'a, Result<Response<HttpBodyStream>, SmugMugError>>;
+
+    /// Performs a GET request for binary data starting at the given byte offset.
+    ///
+    /// The default implementation ignores the offset and returns the whole body.
+    fn get_binary_stream_from<'a>(
+        &'a self,
+        url: &'a str,
+        params: Option<&'a ApiParams<'a>>,
+        _offset: u64,
+    ) -> BoxFuture<'a, Result<Response<RangedBody>, SmugMugError>> {
+        Box::pin(async move {
+            let resp = self.get_binary_stream(url, params).await?;
+            Ok(Response {
+                payload: resp.payload.map(|body| RangedBody { offset: 0, body }),
+                rate_limit: resp.rate_limit,
+            })
+        })
+    }
+}
+
+impl SmugMugApi for Client {
+    fn request<'a>(
+        &'a self,
+        method: Method,
+        url: &'a str,
+        data: Option<Vec<u8>>,
+        params: Option<&'a ApiParams<'a>>,
+    ) -> BoxFuture<'a, Result<Response<Value>, SmugMugError>> {
+        Box::pin(Client::request::<Value>(self, method, url, data, params))
+    }
+
+    fn get_binary_stream<'a>(
+        &'a self,
+        url: &'a str,
+        params: Option<&'a ApiParams<'a>>,
+    ) -> BoxFuture<'a, Result<Response<HttpBodyStream>, SmugMugError>> {
+        Box::pin(Client::get_binary_stream(self, url, params))
+    }
+
+    fn get_binary_stream_from<'a>(
+        &'a self,
+        url: &'a str,
+        params: Option<&'a ApiParams<'a>>,
+        offset: u64,
+    ) -> BoxFuture<'a, Result<Response<RangedBody>, SmugMugError>> {
+        Box::pin(Client::get_binary_stream_from(self, url, params, offset))
+    }
+}
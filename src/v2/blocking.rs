@@ -0,0 +1,494 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+
+//! Synchronous (blocking) versions of the [`crate::v2`] API.
+//!
+//! The blocking [`Client`] owns a small tokio runtime that the async operations are run on, so
+//! consumers don't need to set up their own.  Paginated results are returned as ordinary
+//! [`Iterator`]s.
+//!
+//! *NOTE: These functions must not be called from within an async runtime as they will panic*
+//!
+//! ```rust,no_run
+//! use smugmug::v2::blocking::{Client, User};
+//! use smugmug::v2::{Creds, NodeTypeFilters, SortDirection, SortMethod};
+//!
+//! fn list_albums(creds: Creds) -> anyhow::Result<()> {
+//!     let client = Client::new(creds)?;
+//!     let user_info = User::authenticated_user_info(&client)?;
+//!     let node_info = user_info.node()?;
+//!     for child_album_node in node_info.children(
+//!         NodeTypeFilters::Album,
+//!         SortDirection::Descending,
+//!         SortMethod::Organizer,
+//!     )? {
+//!         let album_info = child_album_node?.album()?;
+//!         for image in album_info.images()? {
+//!             println!("{}", image?.file_name);
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+use crate::v2::{
+    self, ApiParams, CancellationToken, CreateAlbumProps, Creds, DownloadOutcome, DownloadSummary,
+    Method, NodeTypeFilters, RateLimitWindow, Response, SmugMugError, SortDirection, SortMethod,
+};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Synchronous version of [`v2::Client`]
+#[derive(Clone)]
+pub struct Client {
+    inner: v2::Client,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl Client {
+    /// Creates a new SmugMug client instance from the provided credentials
+    pub fn new(creds: Creds) -> Result<Self, SmugMugError> {
+        Self::from_async(v2::Client::new(creds))
+    }
+
+    /// Creates a blocking client from an async client.  This allows for configuring the client
+    /// with [`v2::Client::builder`].
+    pub fn from_async(inner: v2::Client) -> Result<Self, SmugMugError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The async client used by this client
+    pub fn as_async(&self) -> &v2::Client {
+        &self.inner
+    }
+
+    /// Performs a GET request to the SmugMug API
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.block_on(self.inner.get::<T>(url, params))
+    }
+
+    /// Performs a GET request for binary data to the SmugMug API
+    pub fn get_binary_data(
+        &self,
+        url: &str,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<Bytes>, SmugMugError> {
+        self.block_on(self.inner.get_binary_data(url, params))
+    }
+
+    /// Performs a PATCH request to the SmugMug API
+    pub fn patch<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        data: Vec<u8>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.block_on(self.inner.patch::<T>(url, data, params))
+    }
+
+    /// Performs a POST request to the SmugMug API
+    pub fn post<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        data: Vec<u8>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.block_on(self.inner.post::<T>(url, data, params))
+    }
+
+    /// Performs a PUT request to the SmugMug API
+    pub fn put<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        data: Vec<u8>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.block_on(self.inner.put::<T>(url, data, params))
+    }
+
+    /// Performs a DELETE request to the SmugMug API
+    pub fn delete<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.block_on(self.inner.delete::<T>(url, params))
+    }
+
+    /// Performs a request with the given method to the SmugMug API
+    pub fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        data: Option<Vec<u8>>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.block_on(self.inner.request::<T>(method, url, data, params))
+    }
+
+    /// Retrieves the last update for the API rate limit information
+    pub fn get_last_rate_limit_window_update(&self) -> Option<Arc<RateLimitWindow>> {
+        self.inner.get_last_rate_limit_window_update()
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    // Surfaces the stream as an iterator wrapping each item
+    fn iter<'a, T, W>(
+        &self,
+        stream: impl Stream<Item = Result<T, SmugMugError>> + 'a,
+        wrap: impl Fn(T, Client) -> W + 'a,
+    ) -> impl Iterator<Item = Result<W, SmugMugError>> + 'a {
+        let client = self.clone();
+        let mut stream = Box::pin(stream);
+        std::iter::from_fn(move || {
+            client
+                .block_on(stream.next())
+                .map(|v| v.map(|v| wrap(v, client.clone())))
+        })
+    }
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockingApiClient").finish()
+    }
+}
+
+/// Synchronous version of [`v2::User`].  The user information is accessed through [`Deref`].
+#[derive(Debug)]
+pub struct User {
+    inner: v2::User,
+    client: Client,
+}
+
+impl User {
+    /// Returns information for the user at the provided full url
+    pub fn from_url(client: &Client, url: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::User::from_url(client.inner.clone(), url))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the specified user id
+    pub fn from_id(client: &Client, id: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::User::from_id(client.inner.clone(), id))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the authenticated user
+    pub fn authenticated_user_info(client: &Client) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::User::authenticated_user_info(client.inner.clone()))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Retrieves the root node information for this user
+    pub fn node(self) -> Result<Node, SmugMugError> {
+        let inner = self.client.block_on(self.inner.node())?;
+        Ok(Node::wrap(inner, self.client))
+    }
+
+    /// Returns the async version of this user
+    pub fn into_inner(self) -> v2::User {
+        self.inner
+    }
+
+    fn wrap(inner: v2::User, client: Client) -> Self {
+        Self { inner, client }
+    }
+}
+
+impl Deref for User {
+    type Target = v2::User;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Synchronous version of [`v2::Node`].  The node information is accessed through [`Deref`].
+#[derive(Debug, Clone)]
+pub struct Node {
+    inner: v2::Node,
+    client: Client,
+}
+
+impl Node {
+    /// Returns information for the node at the provided full url
+    pub fn from_url(client: &Client, url: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::Node::from_url(client.inner.clone(), url))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the specified node id
+    pub fn from_id(client: &Client, id: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::Node::from_id(client.inner.clone(), id))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the list of node ids
+    pub fn from_id_slice(client: &Client, id_list: &[&str]) -> Result<Vec<Self>, SmugMugError> {
+        let nodes = client.block_on(v2::Node::from_id_slice(client.inner.clone(), id_list))?;
+        Ok(nodes
+            .into_iter()
+            .map(|v| Self::wrap(v, client.clone()))
+            .collect())
+    }
+
+    /// Updates this nodes data fields
+    pub fn update_node_data(&self, data: Vec<u8>) -> Result<Node, SmugMugError> {
+        let inner = self.client.block_on(
+            self.inner
+                .update_node_data_with_client(self.client.inner.clone(), data),
+        )?;
+        Ok(Self::wrap(inner, self.client.clone()))
+    }
+
+    /// Retrieves the Album specific information about this Node
+    pub fn album(&self) -> Result<Album, SmugMugError> {
+        let inner = self.client.block_on(self.inner.album())?;
+        Ok(Album::wrap(inner, self.client.clone()))
+    }
+
+    /// Creates an album off this node
+    pub fn create_album(&self, album_props: CreateAlbumProps) -> Result<Album, SmugMugError> {
+        let inner = self.client.block_on(self.inner.create_album(album_props))?;
+        Ok(Album::wrap(inner, self.client.clone()))
+    }
+
+    /// Retrieves the Child Nodes information for this Node
+    pub fn children(
+        &self,
+        filter_by_type: NodeTypeFilters,
+        sort_direction: SortDirection,
+        sort_method: SortMethod,
+    ) -> Result<impl Iterator<Item = Result<Node, SmugMugError>> + '_, SmugMugError> {
+        let stream = self
+            .inner
+            .children(filter_by_type, sort_direction, sort_method)?;
+        Ok(self.client.iter(stream, Node::wrap))
+    }
+
+    /// Returns the async version of this node
+    pub fn into_inner(self) -> v2::Node {
+        self.inner
+    }
+
+    fn wrap(inner: v2::Node, client: Client) -> Self {
+        Self { inner, client }
+    }
+}
+
+impl Deref for Node {
+    type Target = v2::Node;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Synchronous version of [`v2::Album`].  The album information is accessed through [`Deref`].
+#[derive(Debug, Clone)]
+pub struct Album {
+    inner: v2::Album,
+    client: Client,
+}
+
+impl Album {
+    /// Returns information for the album at the provided full url
+    pub fn from_url(client: &Client, url: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::Album::from_url(client.inner.clone(), url))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the specified album id
+    pub fn from_id(client: &Client, id: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::Album::from_id(client.inner.clone(), id))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the list of album ids
+    pub fn from_id_slice(client: &Client, id_list: &[&str]) -> Result<Vec<Self>, SmugMugError> {
+        let albums = client.block_on(v2::Album::from_id_slice(client.inner.clone(), id_list))?;
+        Ok(albums
+            .into_iter()
+            .map(|v| Self::wrap(v, client.clone()))
+            .collect())
+    }
+
+    /// Retrieves information about the images associated with this Album
+    pub fn images(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<Image, SmugMugError>> + '_, SmugMugError> {
+        let stream = self.inner.images()?;
+        Ok(self.client.iter(stream, Image::wrap))
+    }
+
+    /// Updates this Album's data fields
+    pub fn update_album_data_fields(&self, data: Vec<u8>) -> Result<Album, SmugMugError> {
+        let inner = self
+            .client
+            .block_on(self.inner.update_album_data_fields(data))?;
+        Ok(Self::wrap(inner, self.client.clone()))
+    }
+
+    /// Clear the upload key on this Album
+    pub fn clear_upload_key(&self) -> Result<Album, SmugMugError> {
+        let inner = self.client.block_on(self.inner.clear_upload_key())?;
+        Ok(Self::wrap(inner, self.client.clone()))
+    }
+
+    /// Set the upload key for this Album
+    pub fn set_upload_key(&self, upload_key: &str) -> Result<Album, SmugMugError> {
+        let inner = self
+            .client
+            .block_on(self.inner.set_upload_key(upload_key))?;
+        Ok(Self::wrap(inner, self.client.clone()))
+    }
+
+    /// Returns the async version of this album
+    pub fn into_inner(self) -> v2::Album {
+        self.inner
+    }
+
+    fn wrap(inner: v2::Album, client: Client) -> Self {
+        Self { inner, client }
+    }
+}
+
+impl Deref for Album {
+    type Target = v2::Album;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Synchronous version of [`v2::Image`].  The image information is accessed through [`Deref`].
+#[derive(Debug, Clone)]
+pub struct Image {
+    inner: v2::Image,
+    client: Client,
+}
+
+impl Image {
+    /// Returns information for the image at the provided full url
+    pub fn from_url(client: &Client, url: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::Image::from_url(client.inner.clone(), url))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the specified image id
+    pub fn from_id(client: &Client, id: &str) -> Result<Self, SmugMugError> {
+        let inner = client.block_on(v2::Image::from_id(client.inner.clone(), id))?;
+        Ok(Self::wrap(inner, client.clone()))
+    }
+
+    /// Returns information for the list of image ids
+    pub fn from_id_slice(client: &Client, id_list: &[&str]) -> Result<Vec<Self>, SmugMugError> {
+        let images = client.block_on(v2::Image::from_id_slice(client.inner.clone(), id_list))?;
+        Ok(images
+            .into_iter()
+            .map(|v| Self::wrap(v, client.clone()))
+            .collect())
+    }
+
+    /// Updates this Image data fields
+    pub fn update_image_data(&self, data: Vec<u8>) -> Result<Image, SmugMugError> {
+        let inner = self.client.block_on(
+            self.inner
+                .update_image_data_with_client(self.client.inner.clone(), data),
+        )?;
+        Ok(Self::wrap(inner, self.client.clone()))
+    }
+
+    /// Retrieves the image data found at the archive uri
+    pub fn get_archive(&self) -> Result<Bytes, SmugMugError> {
+        self.client.block_on(
+            self.inner
+                .get_archive_with_client(self.client.inner.clone()),
+        )
+    }
+
+    /// Writes the image data found at the archive uri to the file at the given path without
+    /// holding the whole file in memory
+    pub fn download_archive_to_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadSummary, SmugMugError> {
+        let client = self.client.inner.clone();
+        self.client.block_on(async {
+            let mut file = tokio::fs::File::create(path).await?;
+            self.inner
+                .download_archive_to_with_client(client, &mut file)
+                .await
+        })
+    }
+
+    /// Downloads the image data found at the archive uri to the file at the given path, resuming
+    /// an earlier interrupted download
+    pub fn download_archive_resumable(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadSummary, SmugMugError> {
+        self.client.block_on(
+            self.inner
+                .download_archive_resumable_with_client(self.client.inner.clone(), path),
+        )
+    }
+
+    /// Downloads the image data found at the archive uri to the file at the given path, resuming
+    /// an earlier interrupted download and stopping once the token is cancelled from another
+    /// thread
+    pub fn download_archive_resumable_cancellable(
+        &self,
+        path: impl AsRef<Path>,
+        cancel: &CancellationToken,
+    ) -> Result<DownloadOutcome, SmugMugError> {
+        self.client.block_on(
+            self.inner
+                .download_archive_resumable_cancellable_with_client(
+                    self.client.inner.clone(),
+                    path,
+                    cancel,
+                ),
+        )
+    }
+
+    /// Returns the async version of this image
+    pub fn into_inner(self) -> v2::Image {
+        self.inner
+    }
+
+    fn wrap(inner: v2::Image, client: Client) -> Self {
+        Self { inner, client }
+    }
+}
+
+impl Deref for Image {
+    type Target = v2::Image;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::errors::SmugMugError;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Limit on the number of API calls made through a [`crate::v2::Client`] scope created with
+/// [`crate::v2::Client::with_budget`].
+///
+/// Once the limit is reached further calls fail with [`SmugMugError::BudgetExhausted`] without
+/// being sent.  Each call through the scope counts once, whether it is retried or answered from
+/// a cache.  Mutations skipped in dry-run mode aren't counted while the GET made before a
+/// journaled mutation is.  A budget created from a client that already has one also draws from
+/// the outer budget.
+///
+/// ```rust,no_run
+/// use smugmug::v2::{Client, Creds, User};
+///
+/// # async fn run(creds: Creds) -> Result<(), smugmug::v2::SmugMugError> {
+/// let client = Client::new(creds);
+/// let scoped = client.with_budget(500);
+///
+/// let result = User::authenticated_user_info(scoped.clone()).await;
+///
+/// let budget = scoped.budget().unwrap();
+/// println!(
+///     "Used {} of {} API calls, {} refused",
+///     budget.consumed(),
+///     budget.limit(),
+///     budget.rejected()
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RequestBudget {
+    limit: u64,
+    consumed: AtomicU64,
+    rejected: AtomicU64,
+    parent: Option<Arc<RequestBudget>>,
+}
+
+impl RequestBudget {
+    pub(crate) fn new(limit: u64, parent: Option<Arc<RequestBudget>>) -> Self {
+        Self {
+            limit,
+            consumed: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            parent,
+        }
+    }
+
+    /// Number of API calls allowed
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Number of API calls made so far
+    pub fn consumed(&self) -> u64 {
+        self.consumed.load(Ordering::Relaxed)
+    }
+
+    /// Number of API calls that can still be made
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.consumed())
+    }
+
+    /// Number of API calls refused since the budget was exhausted
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Whether no more API calls can be made
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    // Takes one call from this budget and any outer budgets, failing if any are exhausted
+    pub(crate) fn try_consume(&self) -> Result<(), SmugMugError> {
+        let limit = self.limit;
+        if self
+            .consumed
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                (v < limit).then_some(v + 1)
+            })
+            .is_err()
+        {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(SmugMugError::BudgetExhausted(limit));
+        }
+        if let Some(parent) = self.parent.as_ref()
+            && let Err(err) = parent.try_consume()
+        {
+            // Give the call back since it isn't being made
+            self.consumed.fetch_sub(1, Ordering::Relaxed);
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(err);
+        }
+        Ok(())
+    }
+}
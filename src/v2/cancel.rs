@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::errors::SmugMugError;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Position within a paged listing to resume a cancelled stream from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PagePosition {
+    /// Url of the page holding the next item
+    pub url: String,
+    /// Number of items on that page that were already returned
+    pub offset: usize,
+}
+
+// Shared record of where a stream has got to
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PositionTracker(Arc<Mutex<Option<PagePosition>>>);
+
+impl PositionTracker {
+    pub(crate) fn set(&self, url: &url::Url, offset: usize) {
+        *self.lock() = Some(PagePosition {
+            url: url.to_string(),
+            offset,
+        });
+    }
+
+    pub(crate) fn clear(&self) {
+        *self.lock() = None;
+    }
+
+    fn get(&self) -> Option<PagePosition> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<PagePosition>> {
+        self.0.lock().expect("Failed locking the stream position")
+    }
+}
+
+/// Stream of child objects that ends early once its [`CancellationToken`] is cancelled.
+///
+/// Items returned before the cancellation are kept and [`CancellableStream::resume_position`]
+/// tells where to pick the listing up again.
+///
+/// ```rust,no_run
+/// use futures::StreamExt;
+/// use smugmug::v2::{CancellationToken, Node, NodeTypeFilters, SortDirection, SortMethod};
+///
+/// # async fn run(node: Node) -> Result<(), smugmug::v2::SmugMugError> {
+/// let cancel = CancellationToken::new();
+/// // Hand a clone to a signal handler or UI which calls cancel() on it
+/// let mut children = node.children_cancellable(
+///     NodeTypeFilters::Any,
+///     SortDirection::Ascending,
+///     SortMethod::SortIndex,
+///     cancel.clone(),
+///     None,
+/// )?;
+/// while let Some(child) = children.next().await {
+///     println!("{}", child?.name);
+/// }
+///
+/// // Continue from the same place the next time around
+/// let resume_from = children.resume_position();
+/// let children = node.children_cancellable(
+///     NodeTypeFilters::Any,
+///     SortDirection::Ascending,
+///     SortMethod::SortIndex,
+///     CancellationToken::new(),
+///     resume_from,
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CancellableStream<'a, T> {
+    inner: BoxStream<'a, Result<T, SmugMugError>>,
+    position: PositionTracker,
+}
+
+impl<'a, T> CancellableStream<'a, T> {
+    pub(crate) fn new(
+        inner: impl Stream<Item = Result<T, SmugMugError>> + Send + 'a,
+        position: PositionTracker,
+    ) -> Self {
+        Self {
+            inner: inner.boxed(),
+            position,
+        }
+    }
+
+    /// Position to resume the listing from, or `None` once every item has been returned
+    pub fn resume_position(&self) -> Option<PagePosition> {
+        self.position.get()
+    }
+}
+
+impl<T> Stream for CancellableStream<'_, T> {
+    type Item = Result<T, SmugMugError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl<T> std::fmt::Debug for CancellableStream<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellableStream")
+            .field("position", &self.position.get())
+            .finish()
+    }
+}
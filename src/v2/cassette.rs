@@ -0,0 +1,250 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::errors::SmugMugError;
+use crate::v2::instrument::redact_url;
+use crate::v2::transport::{HttpRequest, HttpResponse, HttpTransport};
+use base64::prelude::*;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Response headers that are never written to a cassette
+const REDACTED_HEADERS: [&str; 2] = ["set-cookie", "www-authenticate"];
+
+/// Whether a [`CassetteTransport`] records traffic or replays it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Sends the requests and writes each request/response pair to the cassette
+    Record,
+    /// Serves the responses from the cassette without using the network
+    Replay,
+}
+
+/// [`HttpTransport`] that records request/response pairs to a cassette file or replays them.
+///
+/// The cassette is a JSON lines file with one request/response pair per line.  The credentials
+/// are redacted before writing: request headers (including `Authorization`) aren't recorded and
+/// the `APIKey` and `oauth_*` query parameters are replaced.  During replay requests are matched
+/// on the method and redacted url, with repeated requests served in the order they were recorded.
+///
+/// A cassette is usually set up with [`crate::v2::ClientBuilder::cassette`].
+///
+/// ```rust,no_run
+/// use smugmug::v2::{CassetteMode, Client, User};
+///
+/// # async fn run() -> Result<(), smugmug::v2::SmugMugError> {
+/// // Record against the live API once
+/// let client = Client::builder()
+///     .cassette("tests/cassettes/user.jsonl", CassetteMode::Record)
+///     .build()?;
+/// User::from_id(client, "apidemo").await?;
+///
+/// // Then replay offline
+/// let client = Client::builder()
+///     .cassette("tests/cassettes/user.jsonl", CassetteMode::Replay)
+///     .build()?;
+/// let user = User::from_id(client, "apidemo").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CassetteTransport {
+    mode: CassetteState,
+}
+
+enum CassetteState {
+    Record {
+        inner: Arc<dyn HttpTransport>,
+        file: Mutex<File>,
+    },
+    Replay {
+        interactions: Mutex<HashMap<String, VecDeque<Interaction>>>,
+    },
+}
+
+// Single request/response pair as written to the cassette
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<String>,
+    status: u16,
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
+}
+
+impl CassetteTransport {
+    /// Creates a transport sending requests with `inner` and recording them to the file at the
+    /// given path, replacing any existing cassette
+    pub fn record(
+        path: impl AsRef<Path>,
+        inner: Arc<dyn HttpTransport>,
+    ) -> Result<Self, SmugMugError> {
+        if let Some(parent) = path.as_ref().parent().filter(|v| !v.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            mode: CassetteState::Record {
+                inner,
+                file: Mutex::new(File::create(path)?),
+            },
+        })
+    }
+
+    /// Creates a transport serving the responses recorded in the file at the given path
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, SmugMugError> {
+        let mut interactions: HashMap<String, VecDeque<Interaction>> = HashMap::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let interaction: Interaction = serde_json::from_str(&line)?;
+            interactions
+                .entry(Self::match_key(&interaction.method, &interaction.url))
+                .or_default()
+                .push_back(interaction);
+        }
+        Ok(Self {
+            mode: CassetteState::Replay {
+                interactions: Mutex::new(interactions),
+            },
+        })
+    }
+
+    /// Mode the transport was created in
+    pub fn mode(&self) -> CassetteMode {
+        match self.mode {
+            CassetteState::Record { .. } => CassetteMode::Record,
+            CassetteState::Replay { .. } => CassetteMode::Replay,
+        }
+    }
+
+    fn match_key(method: &str, url: &str) -> String {
+        format!("{method} {url}")
+    }
+
+    async fn record_interaction(
+        inner: &dyn HttpTransport,
+        file: &Mutex<File>,
+        req: HttpRequest,
+    ) -> Result<HttpResponse, SmugMugError> {
+        let method = req.method.to_string();
+        let url = redact_url(&req.url);
+        let request_body = req
+            .body
+            .as_ref()
+            .map(|v| String::from_utf8_lossy(v).into_owned());
+
+        let resp = inner.send(req).await?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        let (text_body, body_base64) = match std::str::from_utf8(&body) {
+            Ok(text) => (Some(text.to_string()), None),
+            Err(_) => (None, Some(BASE64_STANDARD.encode(&body))),
+        };
+        let interaction = Interaction {
+            method,
+            url,
+            request_body,
+            status: status.as_u16(),
+            headers: headers
+                .iter()
+                .filter(|(name, _)| !REDACTED_HEADERS.contains(&name.as_str()))
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: text_body,
+            body_base64,
+        };
+        let mut line = serde_json::to_vec(&interaction)?;
+        line.push(b'\n');
+        file.lock()
+            .expect("Failed locking the cassette file")
+            .write_all(&line)?;
+
+        Ok(HttpResponse::from_bytes(status, headers, body))
+    }
+
+    fn replay_interaction(
+        interactions: &Mutex<HashMap<String, VecDeque<Interaction>>>,
+        req: &HttpRequest,
+    ) -> Result<HttpResponse, SmugMugError> {
+        let key = Self::match_key(req.method.as_str(), &redact_url(&req.url));
+        let interaction = interactions
+            .lock()
+            .expect("Failed locking the cassette interactions")
+            .get_mut(&key)
+            .and_then(|v| v.pop_front())
+            .ok_or_else(|| SmugMugError::CassetteMismatch(key.clone()))?;
+
+        let status = StatusCode::from_u16(interaction.status)
+            .map_err(|_| SmugMugError::CassetteMismatch(key.clone()))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in interaction.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        let body = match (interaction.body, interaction.body_base64) {
+            (Some(body), _) => Bytes::from(body),
+            (None, Some(body)) => BASE64_STANDARD
+                .decode(body)
+                .map_err(|_| SmugMugError::CassetteMismatch(key))?
+                .into(),
+            (None, None) => Bytes::new(),
+        };
+        Ok(HttpResponse::from_bytes(status, headers, body))
+    }
+}
+
+impl HttpTransport for CassetteTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+        Box::pin(async move {
+            match &self.mode {
+                CassetteState::Record { inner, file } => {
+                    Self::record_interaction(inner.as_ref(), file, req).await
+                }
+                CassetteState::Replay { interactions } => {
+                    Self::replay_interaction(interactions, &req)
+                }
+            }
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        match &self.mode {
+            CassetteState::Record { inner, .. } => inner.sleep(duration),
+            CassetteState::Replay { .. } => Box::pin(tokio::time::sleep(duration)),
+        }
+    }
+}
+
+impl std::fmt::Debug for CassetteTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CassetteTransport")
+            .field("mode", &self.mode())
+            .finish()
+    }
+}
@@ -5,32 +5,60 @@
  *  - MIT license <http://opensource.org/licenses/MIT>
  *  at your option.
  */
+use crate::v2::api::SmugMugApi;
+use crate::v2::budget::RequestBudget;
+use crate::v2::cassette::{CassetteMode, CassetteTransport};
+use crate::v2::download::RangedBody;
 use crate::v2::errors::SmugMugError;
+use crate::v2::http_cache::{CachedResponse, HttpCache};
+use crate::v2::instrument::{CallSpan, endpoint_template, redact_url};
+use crate::v2::interceptor::Interceptor;
+use crate::v2::journal::{InverseRequest, JournalEntry, MutationJournal};
+use crate::v2::metrics::{MetricsSnapshot, RequestMetrics};
+use crate::v2::object_cache::ObjectCache;
+use crate::v2::rate_limit_store::{InMemoryRateLimitStore, RateLimitStore};
+use crate::v2::retry::RetryPolicy;
+use crate::v2::throttle::ThrottlePolicy;
+use crate::v2::transport::{
+    HttpBodyStream, HttpRequest, HttpResponse, HttpTransport, ReqwestTransport, collect_body,
+};
 use base64::prelude::*;
 use bytes::Bytes;
 use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures::StreamExt;
 use hmac::{Hmac, Mac};
+use http::StatusCode;
+use http::header::{
+    ACCEPT, AUTHORIZATION, CONTENT_RANGE, CONTENT_TYPE, ETAG, HeaderMap, HeaderValue,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE, USER_AGENT,
+};
 use num_enum::TryFromPrimitive;
-use rand::distr::Alphanumeric;
 use rand::Rng;
-use reqwest::header::HeaderMap;
-use reqwest::Response as ReqwestResponse;
+use rand::distr::Alphanumeric;
 use serde::de::DeserializeOwned;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sha1::Sha1;
 use std::collections::BTreeMap;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use urlencoding::encode as url_encode;
 
+pub use http::Method;
+
 type HmacSha1 = Hmac<Sha1>;
 
 // Root SmugMug API
 pub(crate) const API_ORIGIN: &str = "https://api.smugmug.com";
 
+// Root SmugMug Upload API
+pub(crate) const UPLOAD_ORIGIN: &str = "https://upload.smugmug.com";
+
 /// Handles the lower level communication with the SmugMug REST API.
 #[derive(Default, Clone)]
 pub struct Client {
     inner: Arc<ClientRef>,
+    api: Option<Arc<dyn SmugMugApi>>,
+    budget: Option<Arc<RequestBudget>>,
 }
 
 impl Client {
@@ -38,16 +66,72 @@ impl Client {
     pub fn new(creds: Creds) -> Self {
         Self {
             inner: Arc::new(ClientRef::new(creds)),
+            api: None,
+            budget: None,
+        }
+    }
+
+    /// Creates a client that sends all requests through the given [`SmugMugApi`] instead of
+    /// HTTP.  This allows substituting a fake returning canned objects in tests of code taking a
+    /// [`Client`].
+    pub fn from_api(api: Arc<dyn SmugMugApi>) -> Self {
+        Self {
+            inner: Arc::new(ClientRef::default()),
+            api: Some(api),
+            budget: None,
         }
     }
 
+    /// Creates a builder for configuring a SmugMug client instance
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Returns a client sharing this one's configuration and rate limits that allows at most
+    /// `limit` API calls.  Objects retrieved through it keep using it, so a whole tree walk can
+    /// be bounded.  See [`RequestBudget`].
+    pub fn with_budget(&self, limit: u64) -> Client {
+        Client {
+            budget: Some(Arc::new(RequestBudget::new(limit, self.budget.clone()))),
+            ..self.clone()
+        }
+    }
+
+    /// The budget of API calls this client was created with by [`Client::with_budget`]
+    pub fn budget(&self) -> Option<Arc<RequestBudget>> {
+        self.budget.clone()
+    }
+
+    // Takes a call from the budget if there is one
+    fn consume_budget(&self) -> Result<(), SmugMugError> {
+        match self.budget.as_ref() {
+            Some(budget) => budget.try_consume(),
+            None => Ok(()),
+        }
+    }
+
+    /// The origin that API requests are sent to
+    pub fn api_origin(&self) -> &url::Url {
+        &self.inner.api_origin
+    }
+
+    /// The origin that uploads are sent to
+    pub fn upload_origin(&self) -> &url::Url {
+        &self.inner.upload_origin
+    }
+
+    // Resolves the given uri against the configured API origin
+    pub(crate) fn api_url(&self, uri: &str) -> Result<url::Url, SmugMugError> {
+        Ok(self.inner.api_origin.join(uri)?)
+    }
+
     /// Performs a GET request to the SmugMug API
     pub async fn get<T: DeserializeOwned>(
         &self,
         url: &str,
         params: Option<&ApiParams<'_>>,
     ) -> Result<Response<T>, SmugMugError> {
-        self.inner.get::<T>(url, params).await
+        self.request::<T>(Method::GET, url, None, params).await
     }
 
     /// Performs a GET request for binary data to the SmugMug API
@@ -56,7 +140,47 @@ impl Client {
         url: &str,
         params: Option<&ApiParams<'_>>,
     ) -> Result<Response<Bytes>, SmugMugError> {
-        self.inner.get_binary_data(url, params).await
+        let resp = self.get_binary_stream(url, params).await?;
+
+        // Pull out the payload
+        let payload = match resp.payload {
+            Some(stream) => Some(collect_body(stream).await?),
+            None => None,
+        };
+        Ok(Response {
+            payload,
+            rate_limit: resp.rate_limit,
+        })
+    }
+
+    /// Performs a GET request for binary data to the SmugMug API returning the data as a stream
+    /// of chunks instead of reading it all into memory
+    pub async fn get_binary_stream(
+        &self,
+        url: &str,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<HttpBodyStream>, SmugMugError> {
+        self.consume_budget()?;
+        match self.api.as_ref() {
+            Some(api) => api.get_binary_stream(url, params).await,
+            None => self.inner.get_binary_stream(url, params).await,
+        }
+    }
+
+    /// Performs a GET request for binary data starting at the given byte offset using a `Range`
+    /// request.  The returned [`RangedBody::offset`] is where the returned data starts, which is
+    /// 0 if the server sent the whole body instead.
+    pub async fn get_binary_stream_from(
+        &self,
+        url: &str,
+        params: Option<&ApiParams<'_>>,
+        offset: u64,
+    ) -> Result<Response<RangedBody>, SmugMugError> {
+        self.consume_budget()?;
+        match self.api.as_ref() {
+            Some(api) => api.get_binary_stream_from(url, params, offset).await,
+            None => self.inner.get_binary_stream_from(url, params, offset).await,
+        }
     }
 
     /// Performs a PATCH request to the SmugMug API
@@ -66,7 +190,8 @@ impl Client {
         data: Vec<u8>,
         params: Option<&ApiParams<'_>>,
     ) -> Result<Response<T>, SmugMugError> {
-        self.inner.patch::<T>(url, data, params).await
+        self.request::<T>(Method::PATCH, url, Some(data), params)
+            .await
     }
 
     /// Performs a POST request to the SmugMug API
@@ -76,34 +201,556 @@ impl Client {
         data: Vec<u8>,
         params: Option<&ApiParams<'_>>,
     ) -> Result<Response<T>, SmugMugError> {
-        self.inner.post::<T>(url, data, params).await
+        self.request::<T>(Method::POST, url, Some(data), params)
+            .await
     }
 
-    /// Retrieves the last update for the API rate limit information.  This will return none if
-    /// a get/post/patch API call hasn't been made yet.
+    /// Performs a PUT request to the SmugMug API
+    pub async fn put<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        data: Vec<u8>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.request::<T>(Method::PUT, url, Some(data), params)
+            .await
+    }
+
+    /// Performs a DELETE request to the SmugMug API
+    pub async fn delete<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.request::<T>(Method::DELETE, url, None, params).await
+    }
+
+    /// Performs a request with the given method to the SmugMug API.
     ///
-    /// *NOTE: This isn't updated after [`Self::get_binary_data`] calls used in retrieving images/videos*
-    pub fn get_last_rate_limit_window_update(&self) -> Option<Arc<RateLimitWindow>> {
-        let rate_window = self
-            .inner
-            .last_rate_window
-            .read()
-            .expect("Failed read locking for last rate window update")
-            .clone();
-        if rate_window.is_valid() {
-            Some(rate_window)
-        } else {
-            None
+    /// This allows for calling the parts of the API that may not be implemented yet in the higher
+    /// level interfaces.  The request is signed the same as the other requests and the JSON
+    /// response is handled the same.
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        data: Option<Vec<u8>>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        if self.inner.dry_run && method != Method::GET {
+            return self.dry_run_request(method, url, data, params).await;
+        }
+        if let Some(journal) = self.inner.journal.as_ref()
+            && method != Method::GET
+        {
+            return self
+                .journaled_request(journal, method, url, data, params)
+                .await;
+        }
+        self.dispatch::<T>(method, url, data, params).await
+    }
+
+    /// Reverts the mutations recorded in the journal entries, most recent first.  A PATCH or PUT
+    /// is reverted by patching the changed fields back to their values before and a POST by
+    /// deleting the object it created.  Nothing is sent if any of the entries can't be undone.
+    /// Returns the number of mutations reverted.  If reverting one fails the rest are skipped and
+    /// [`SmugMugError::UndoIncomplete`] reports how many had already been reverted.
+    ///
+    /// See [`MutationJournal`].
+    pub async fn undo(&self, entries: &[JournalEntry]) -> Result<usize, SmugMugError> {
+        let inverses = entries
+            .iter()
+            .rev()
+            .map(JournalEntry::inverse)
+            .collect::<Result<Vec<_>, _>>()?;
+        for (reverted, inverse) in inverses.iter().enumerate() {
+            self.send_inverse(inverse)
+                .await
+                .map_err(|err| SmugMugError::UndoIncomplete(reverted, Box::new(err)))?;
         }
+        Ok(inverses.len())
+    }
+
+    // Sends the request reverting a journaled mutation
+    async fn send_inverse(&self, inverse: &InverseRequest) -> Result<(), SmugMugError> {
+        let req_url = self.api_url(&inverse.uri)?;
+        self.request::<serde_json::Value>(
+            inverse.method.clone(),
+            req_url.as_str(),
+            inverse.data.clone(),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    // Sends the request through the substituted api if there is one
+    async fn dispatch<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        data: Option<Vec<u8>>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        self.consume_budget()?;
+        let Some(api) = self.api.as_ref() else {
+            return self.inner.request::<T>(method, url, data, params).await;
+        };
+        Self::from_value_response(api.request(method, url, data, params).await?)
+    }
+
+    // Makes the mutation recording it along with the object state before, then its outcome
+    async fn journaled_request<T: DeserializeOwned>(
+        &self,
+        journal: &MutationJournal,
+        method: Method,
+        url: &str,
+        data: Option<Vec<u8>>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        let before = match method {
+            Method::POST => None,
+            _ => {
+                // The cached object may be out of date so always fetch what is being replaced
+                if let Some(object_cache) = self.inner.object_cache.as_ref() {
+                    object_cache.invalidate(url);
+                }
+                self.dispatch::<serde_json::Value>(Method::GET, url, None, params)
+                    .await?
+                    .payload
+            }
+        };
+        let sequence = journal.begin(&method, url, data.as_deref(), before).await;
+        let result = self
+            .dispatch::<serde_json::Value>(method, url, data, params)
+            .await;
+        if let Some(sequence) = sequence {
+            // Failures are recorded as well since the server may have applied the change
+            let (after, error) = match &result {
+                Ok(resp) => (resp.payload.clone(), None),
+                Err(err) => (None, Some(err.to_string())),
+            };
+            journal.complete(sequence, after, error).await;
+        }
+        Self::from_value_response(result?)
+    }
+
+    // Converts the JSON payload to the requested type
+    fn from_value_response<T: DeserializeOwned>(
+        resp: Response<serde_json::Value>,
+    ) -> Result<Response<T>, SmugMugError> {
+        Ok(Response {
+            payload: resp
+                .payload
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(SmugMugError::ApiResponseMalformed)?,
+            rate_limit: resp.rate_limit,
+        })
+    }
+
+    /// Returns true if mutating requests are logged instead of sent.  See
+    /// [`ClientBuilder::dry_run`].
+    pub fn is_dry_run(&self) -> bool {
+        self.inner.dry_run
+    }
+
+    // Logs the mutating request and builds the result it would have had.  A PATCH or PUT returns
+    // the current object with the requested changes applied, anything else returns no payload.
+    async fn dry_run_request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        data: Option<Vec<u8>>,
+        params: Option<&ApiParams<'_>>,
+    ) -> Result<Response<T>, SmugMugError> {
+        log::info!(
+            "Dry run, not sending {} {} {}",
+            method,
+            url,
+            data.as_deref()
+                .map(String::from_utf8_lossy)
+                .unwrap_or_default()
+        );
+        if method != Method::PATCH && method != Method::PUT {
+            return Ok(Response {
+                payload: None,
+                rate_limit: None,
+            });
+        }
+
+        let current = Box::pin(self.request::<serde_json::Value>(Method::GET, url, None, params))
+            .await?
+            .payload
+            .ok_or(SmugMugError::ResponseMissing())?;
+        let changes: serde_json::Value = match data {
+            Some(data) => serde_json::from_slice(&data)?,
+            None => serde_json::Value::Null,
+        };
+        Ok(Response {
+            payload: Some(
+                serde_json::from_value(Self::apply_changes(current, changes))
+                    .map_err(SmugMugError::ApiResponseMalformed)?,
+            ),
+            rate_limit: None,
+        })
+    }
+
+    // Applies the changes to the object held in the response
+    fn apply_changes(
+        mut response: serde_json::Value,
+        changes: serde_json::Value,
+    ) -> serde_json::Value {
+        let serde_json::Value::Object(changes) = changes else {
+            return response;
+        };
+        let key = response_object_key(&response);
+        if let Some(object) = key
+            .and_then(|v| response.get_mut(&v))
+            .and_then(|v| v.as_object_mut())
+        {
+            object.extend(changes);
+        }
+        response
+    }
+
+    /// Retrieves the last update for the API rate limit information.  This will return none if
+    /// an API call hasn't been made yet.
+    pub fn get_last_rate_limit_window_update(&self) -> Option<Arc<RateLimitWindow>> {
+        self.inner.last_rate_window.load().filter(|v| v.is_valid())
+    }
+
+    /// Snapshot of the request metrics collected by the client.  See [`MetricsSnapshot`].
+    pub fn metrics(&self) -> MetricsSnapshot {
+        let num_remaining_requests = self
+            .get_last_rate_limit_window_update()
+            .and_then(|v| v.num_remaining_requests());
+        self.inner.metrics.snapshot(num_remaining_requests)
     }
 }
 
-// Internal representation of the client
+// Parses an origin, rejecting anything after the host and port since it would be lost when the
+// absolute API uris are joined to it
+fn parse_origin(origin: &str) -> Result<url::Url, SmugMugError> {
+    let url = url::Url::parse(origin)?;
+    if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+        return Err(SmugMugError::InvalidConfig(format!(
+            "Origin can't have a path, query or fragment: {origin}"
+        )));
+    }
+    Ok(url)
+}
+
+/// Builder used for configuring a [`Client`]
+///
+/// ```rust
+/// use smugmug::v2::{Client, Creds};
+/// use std::time::Duration;
+///
+/// let client = Client::builder()
+///     .creds(Creds::from_tokens("api_key", None, None, None))
+///     .api_origin("http://localhost:8080")
+///     .timeout(Duration::from_secs(30))
+///     .user_agent("my-app/1.0")
+///     .build()
+///     .unwrap();
+/// assert_eq!(client.api_origin().as_str(), "http://localhost:8080/");
+/// ```
 #[derive(Default)]
+pub struct ClientBuilder {
+    creds: Creds,
+    api_origin: Option<String>,
+    upload_origin: Option<String>,
+    timeout: Option<std::time::Duration>,
+    connect_timeout: Option<std::time::Duration>,
+    user_agent: Option<String>,
+    https_client: Option<reqwest::Client>,
+    transport: Option<Arc<dyn HttpTransport>>,
+    retry_policy: Option<RetryPolicy>,
+    throttle_policy: Option<ThrottlePolicy>,
+    rate_limit_store: Option<Arc<dyn RateLimitStore>>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    http_cache: Option<Arc<dyn HttpCache>>,
+    object_cache: Option<Arc<ObjectCache>>,
+    offline: bool,
+    cassette: Option<(std::path::PathBuf, CassetteMode)>,
+    max_concurrent_requests: Option<usize>,
+    max_concurrent_downloads: Option<usize>,
+    dry_run: bool,
+    journal: Option<Arc<MutationJournal>>,
+}
+
+impl ClientBuilder {
+    /// Credentials used for accessing/signing requests
+    pub fn creds(mut self, creds: Creds) -> Self {
+        self.creds = creds;
+        self
+    }
+
+    /// Origin the API requests are sent to.  Defaults to `https://api.smugmug.com`
+    ///
+    /// *NOTE: Only a scheme, host and port are allowed.  The API uris are absolute paths so a
+    /// path on the origin would be dropped, [`Self::build`] fails if one is given.*
+    pub fn api_origin(mut self, origin: &str) -> Self {
+        self.api_origin = Some(origin.into());
+        self
+    }
+
+    /// Origin the uploads are sent to.  Defaults to `https://upload.smugmug.com`
+    ///
+    /// *NOTE: As with [`Self::api_origin`] a path isn't allowed.*
+    pub fn upload_origin(mut self, origin: &str) -> Self {
+        self.upload_origin = Some(origin.into());
+        self
+    }
+
+    /// Total time allowed for a request before it is failed
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Time allowed for the connection phase of a request.
+    ///
+    /// *NOTE: This is ignored if an http client is provided with [`Self::http_client`] or a
+    /// transport is provided with [`Self::transport`]*
+    pub fn connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// User agent sent with each request
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Pre-configured http client to use (proxy, custom root certs, etc.)
+    pub fn http_client(mut self, https_client: reqwest::Client) -> Self {
+        self.https_client = Some(https_client);
+        self
+    }
+
+    /// Transport used for sending requests.  Defaults to [`ReqwestTransport`].
+    ///
+    /// *NOTE: This takes precedence over [`Self::http_client`]*
+    pub fn transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Retries requests failing with a 429, 5xx or connection error per the given policy.
+    ///
+    /// This applies to all requests including each page retrieved while streaming children.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Slows down or pauses requests as the rate limit window runs out per the given policy.
+    pub fn throttle_policy(mut self, throttle_policy: ThrottlePolicy) -> Self {
+        self.throttle_policy = Some(throttle_policy);
+        self
+    }
+
+    /// Store used for recording the rate limit window.  Sharing a store between clients lets
+    /// them coordinate on a single rate limit budget.  Defaults to a per client
+    /// [`InMemoryRateLimitStore`].
+    pub fn rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.rate_limit_store = Some(store);
+        self
+    }
+
+    /// Adds an interceptor that is called with each outgoing request and incoming response.
+    /// Interceptors are called in the order they are added.
+    pub fn interceptor(mut self, interceptor: Arc<dyn Interceptor>) -> Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    /// Cache used for conditional GET requests.  Responses with an `ETag` or `Last-Modified`
+    /// header are stored and revalidated on later requests, serving the cached body when the
+    /// server replies with `304 Not Modified`.  Snapshot caches such as
+    /// [`crate::v2::DiskHttpCache`] store every successful response.
+    pub fn http_cache(mut self, http_cache: Arc<dyn HttpCache>) -> Self {
+        self.http_cache = Some(http_cache);
+        self
+    }
+
+    /// Cache of GET responses served without making a request until they expire.  Updates made
+    /// through the client invalidate the cached entries for the same uri.
+    pub fn object_cache(mut self, object_cache: Arc<ObjectCache>) -> Self {
+        self.object_cache = Some(object_cache);
+        self
+    }
+
+    /// Serves GET requests only from the [`Self::http_cache`] and [`Self::object_cache`] without
+    /// using the network.  Anything not cached fails with [`SmugMugError::OfflineCacheMiss`].
+    ///
+    /// *NOTE: PATCH, POST, PUT and DELETE requests and binary downloads, such as the image
+    /// archive, are never cached so they fail with [`SmugMugError::OfflineUnsupported`].*
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Records the traffic to the cassette file at the given path or replays it from there.  See
+    /// [`CassetteTransport`] for details.
+    ///
+    /// *NOTE: When recording, the requests are sent with the transport that would otherwise be
+    /// used.  When replaying, no requests are sent.*
+    pub fn cassette(mut self, path: impl AsRef<std::path::Path>, mode: CassetteMode) -> Self {
+        self.cassette = Some((path.as_ref().to_path_buf(), mode));
+        self
+    }
+
+    /// Maximum number of JSON API requests in flight at once across all clones of the client.
+    /// Further requests wait for one to complete.  Unlimited if not set.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// Maximum number of binary transfers in flight at once across all clones of the client.
+    /// A transfer counts until its body stream has been dropped.  Unlimited if not set.
+    pub fn max_concurrent_downloads(mut self, max: usize) -> Self {
+        self.max_concurrent_downloads = Some(max);
+        self
+    }
+
+    /// Logs PATCH, POST, PUT and DELETE requests instead of sending them so a run can be
+    /// rehearsed.  A PATCH or PUT returns the current object with the requested changes applied
+    /// while the other methods return no payload.  [`crate::v2::Node::create_album`] returns an
+    /// album built from the requested properties.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Journal recording every mutation made through the client so it can be undone.  See
+    /// [`MutationJournal`].
+    ///
+    /// *NOTE: Each PATCH, PUT and DELETE is preceded by a GET of the object to record its state
+    /// before the change.*
+    pub fn journal(mut self, journal: Arc<MutationJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Creates the client from the configured values
+    pub fn build(self) -> Result<Client, SmugMugError> {
+        let transport: Arc<dyn HttpTransport> = match (self.transport, self.https_client) {
+            (Some(transport), _) => transport,
+            (None, Some(https_client)) => Arc::new(ReqwestTransport::new(https_client)),
+            (None, None) => {
+                let mut builder = reqwest::Client::builder();
+                if let Some(connect_timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(connect_timeout);
+                }
+                Arc::new(ReqwestTransport::new(builder.build()?))
+            }
+        };
+        let transport: Arc<dyn HttpTransport> = match self.cassette {
+            Some((path, CassetteMode::Record)) => {
+                Arc::new(CassetteTransport::record(path, transport)?)
+            }
+            Some((path, CassetteMode::Replay)) => Arc::new(CassetteTransport::replay(path)?),
+            None => transport,
+        };
+
+        let user_agent = self
+            .user_agent
+            .map(|v| {
+                HeaderValue::from_str(&v)
+                    .map_err(|_| SmugMugError::InvalidConfig(format!("Invalid user agent: {v}")))
+            })
+            .transpose()?;
+
+        Ok(Client {
+            api: None,
+            budget: None,
+            inner: Arc::new(ClientRef {
+                creds: self.creds,
+                transport,
+                api_origin: parse_origin(self.api_origin.as_deref().unwrap_or(API_ORIGIN))?,
+                upload_origin: parse_origin(
+                    self.upload_origin.as_deref().unwrap_or(UPLOAD_ORIGIN),
+                )?,
+                timeout: self.timeout,
+                user_agent,
+                retry_policy: self.retry_policy,
+                throttle_policy: self.throttle_policy,
+                last_rate_window: self
+                    .rate_limit_store
+                    .unwrap_or_else(|| Arc::new(InMemoryRateLimitStore::default())),
+                interceptors: self.interceptors,
+                http_cache: self.http_cache,
+                object_cache: self.object_cache,
+                offline: self.offline,
+                metrics: RequestMetrics::default(),
+                json_limit: self
+                    .max_concurrent_requests
+                    .map(|v| Arc::new(Semaphore::new(v.max(1)))),
+                binary_limit: self
+                    .max_concurrent_downloads
+                    .map(|v| Arc::new(Semaphore::new(v.max(1)))),
+                dry_run: self.dry_run,
+                journal: self.journal,
+            }),
+        })
+    }
+}
+
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("creds", &self.creds)
+            .field("api_origin", &self.api_origin)
+            .field("upload_origin", &self.upload_origin)
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("user_agent", &self.user_agent)
+            .field("retry_policy", &self.retry_policy)
+            .field("throttle_policy", &self.throttle_policy)
+            .field("interceptors", &self.interceptors.len())
+            .field("http_cache", &self.http_cache.is_some())
+            .field("object_cache", &self.object_cache)
+            .field("offline", &self.offline)
+            .field("cassette", &self.cassette)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field("max_concurrent_downloads", &self.max_concurrent_downloads)
+            .field("dry_run", &self.dry_run)
+            .field("journal", &self.journal.as_ref().map(|v| v.path()))
+            .finish()
+    }
+}
+
+// Internal representation of the client
 struct ClientRef {
     creds: Creds,
-    https_client: reqwest::Client,
-    last_rate_window: RwLock<Arc<RateLimitWindow>>,
+    transport: Arc<dyn HttpTransport>,
+    api_origin: url::Url,
+    upload_origin: url::Url,
+    timeout: Option<std::time::Duration>,
+    user_agent: Option<HeaderValue>,
+    retry_policy: Option<RetryPolicy>,
+    throttle_policy: Option<ThrottlePolicy>,
+    last_rate_window: Arc<dyn RateLimitStore>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    http_cache: Option<Arc<dyn HttpCache>>,
+    object_cache: Option<Arc<ObjectCache>>,
+    offline: bool,
+    metrics: RequestMetrics,
+    json_limit: Option<Arc<Semaphore>>,
+    binary_limit: Option<Arc<Semaphore>>,
+    dry_run: bool,
+    journal: Option<Arc<MutationJournal>>,
+}
+
+impl Default for ClientRef {
+    fn default() -> Self {
+        Self::new(Creds::default())
+    }
 }
 
 impl ClientRef {
@@ -111,188 +758,500 @@ impl ClientRef {
     fn new(creds: Creds) -> Self {
         Self {
             creds,
-            https_client: reqwest::Client::new(),
-            last_rate_window: RwLock::new(Arc::new(RateLimitWindow {
-                ..Default::default()
-            })),
+            transport: Arc::new(ReqwestTransport::default()),
+            api_origin: url::Url::parse(API_ORIGIN).expect("API origin is a valid url"),
+            upload_origin: url::Url::parse(UPLOAD_ORIGIN).expect("Upload origin is a valid url"),
+            timeout: None,
+            user_agent: None,
+            retry_policy: None,
+            throttle_policy: None,
+            last_rate_window: Arc::new(InMemoryRateLimitStore::default()),
+            interceptors: Vec::new(),
+            http_cache: None,
+            object_cache: None,
+            offline: false,
+            metrics: RequestMetrics::default(),
+            json_limit: None,
+            binary_limit: None,
+            dry_run: false,
+            journal: None,
         }
     }
 
-    // Performs a GET request to the SmugMug API
-    async fn get<T: DeserializeOwned>(
+    // Performs a GET request for binary data to the SmugMug API returning the body as a stream
+    async fn get_binary_stream(
         &self,
         url: &str,
         params: Option<&ApiParams<'_>>,
-    ) -> Result<Response<T>, SmugMugError> {
+    ) -> Result<Response<HttpBodyStream>, SmugMugError> {
         let req_url = self.create_req(url, params)?;
+        let permit = Self::acquire(self.binary_limit.as_ref()).await;
+        let resp = self
+            .send(Method::GET, &req_url, false, HeaderMap::new(), None)
+            .await?;
 
-        // If we are in read-only mode we have to do this a little different.  Since other functions
-        // require Oauth1 singing, this is only needed for get.
-        let resp = if self.creds.are_all_tokens_available() {
-            let auth_header = self.creds.create_oauth1_header("GET", &req_url)?;
-            self.https_client
-                .clone()
-                .get(req_url)
-                .header("Accept", "application/json")
-                .header("Authorization", auth_header)
-                .send()
-                .await?
-        } else {
-            self.https_client
-                .clone()
-                .get(req_url)
-                .header("Accept", "application/json")
-                .send()
-                .await?
-        };
-        self.handle_json_response(resp).await
+        // Get current rate limit values
+        let rate_limit = self.extract_rate_limits_from_response(&resp);
+
+        // Check if the http error code returned was an error
+        self.error_on_http_status(&req_url, &resp, Some(&rate_limit))?;
+
+        Ok(Response {
+            payload: Some(Self::hold_permit(resp.into_body_stream(), permit)),
+            rate_limit: Some(rate_limit),
+        })
     }
 
-    // Performs a GET request for binary data to the SmugMug API
-    async fn get_binary_data(
+    // Performs a GET request for binary data starting at the given byte offset
+    async fn get_binary_stream_from(
         &self,
         url: &str,
         params: Option<&ApiParams<'_>>,
-    ) -> Result<Response<Bytes>, SmugMugError> {
+        offset: u64,
+    ) -> Result<Response<RangedBody>, SmugMugError> {
         let req_url = self.create_req(url, params)?;
+        let permit = Self::acquire(self.binary_limit.as_ref()).await;
+        let mut headers = HeaderMap::new();
+        if offset > 0 {
+            headers.insert(
+                RANGE,
+                HeaderValue::from_str(&format!("bytes={offset}-"))
+                    .expect("range header is always valid"),
+            );
+        }
+        let resp = self
+            .send(Method::GET, &req_url, false, headers, None)
+            .await?;
 
-        // If we are in read-only mode we have to do this a little different.  Since other functions
-        // require Oauth1 singing, this is only needed for get.
-        let resp = if self.creds.are_all_tokens_available() {
-            let auth_header = self.creds.create_oauth1_header("GET", &req_url)?;
-            self.https_client
-                .clone()
-                .get(req_url)
-                .header("Authorization", auth_header)
-                .send()
-                .await?
-        } else {
-            self.https_client.clone().get(req_url).send().await?
-        };
+        // Get current rate limit values
+        let rate_limit = self.extract_rate_limits_from_response(&resp);
 
-        // Rate Limits aren't returned for this kind of call
+        // Asking for the bytes after the end of a complete file gets a 416 reporting the full size,
+        // so there is nothing left to send
+        if resp.status() == StatusCode::RANGE_NOT_SATISFIABLE
+            && offset > 0
+            && resp
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("bytes */"))
+                .and_then(|v| v.parse::<u64>().ok())
+                == Some(offset)
+        {
+            return Ok(Response {
+                payload: Some(RangedBody {
+                    offset,
+                    body: Box::pin(futures::stream::empty()),
+                }),
+                rate_limit: Some(rate_limit),
+            });
+        }
 
         // Check if the http error code returned was an error
-        self.error_on_http_status(&resp, None)?;
+        self.error_on_http_status(&req_url, &resp, Some(&rate_limit))?;
+
+        // Servers that don't support ranges send the whole body back with a 200
+        let offset = if resp.status() == StatusCode::PARTIAL_CONTENT {
+            resp.headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("bytes "))
+                .and_then(|v| v.split('-').next())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(offset)
+        } else {
+            0
+        };
 
-        // Pull out the payload
-        match resp.bytes().await {
-            Ok(body) => Ok(Response {
-                payload: Some(body),
-                rate_limit: None,
+        Ok(Response {
+            payload: Some(RangedBody {
+                offset,
+                body: Self::hold_permit(resp.into_body_stream(), permit),
             }),
-            Err(err) => Err(err.into()),
-        }
+            rate_limit: Some(rate_limit),
+        })
     }
 
-    // Performs a PATCH request to the SmugMug API
-    async fn patch<T: DeserializeOwned>(
+    // Performs a request with the given method to the SmugMug API expecting a JSON response
+    async fn request<T: DeserializeOwned>(
         &self,
+        method: Method,
         url: &str,
-        data: Vec<u8>,
+        data: Option<Vec<u8>>,
         params: Option<&ApiParams<'_>>,
     ) -> Result<Response<T>, SmugMugError> {
         let req_url = self.create_req(url, params)?;
-        let auth_header = self.creds.create_oauth1_header("PATCH", &req_url)?;
-        let resp = self
-            .https_client
-            .clone()
-            .patch(req_url)
-            .header("Accept", "application/json")
-            .header("Content-Type", "application/json")
-            .header("Authorization", auth_header)
-            .body(data)
-            .send()
-            .await?;
-        self.handle_json_response(resp).await
+        let is_get = method == Method::GET;
+        let object_cache = self.object_cache.as_ref();
+        if is_get && let Some(body) = object_cache.and_then(|v| v.get(&req_url)) {
+            return Ok(Response {
+                payload: Self::parse_json_payload(&body)?,
+                rate_limit: None,
+            });
+        }
+
+        let _permit = Self::acquire(self.json_limit.as_ref()).await;
+        let http_cache = self.http_cache.as_ref().filter(|_| is_get);
+        // Keyed without the credentials so they are never written to a cache
+        let cache_key = redact_url(&req_url);
+        let cached = http_cache.and_then(|v| v.get(&cache_key));
+
+        // Send the validators so the server can skip resending an unchanged body
+        let mut headers = HeaderMap::new();
+        if let Some(cached) = cached.as_ref() {
+            let validators = [
+                (IF_NONE_MATCH, cached.etag.as_deref()),
+                (IF_MODIFIED_SINCE, cached.last_modified.as_deref()),
+            ];
+            for (name, value) in validators {
+                if let Some(value) = value.and_then(|v| HeaderValue::from_str(v).ok()) {
+                    headers.insert(name, value);
+                }
+            }
+        }
+
+        let mut resp = match cached {
+            // Serve from the snapshot without revalidating
+            Some(cached) if self.offline => {
+                HttpResponse::from_bytes(StatusCode::OK, HeaderMap::new(), cached.body)
+            }
+            cached => {
+                let resp = self
+                    .send(method, &req_url, true, headers, data.map(Bytes::from))
+                    .await?;
+                match http_cache {
+                    Some(http_cache) => {
+                        Self::apply_http_cache(http_cache.as_ref(), &cache_key, cached, resp)
+                            .await?
+                    }
+                    None => resp,
+                }
+            }
+        };
+        // Hold on to the body so it can be cached once it is known to parse
+        let mut cacheable = None;
+        if object_cache.is_some() && is_get && resp.status() == StatusCode::OK {
+            let headers = resp.headers().clone();
+            let body = resp.bytes().await?;
+            cacheable = Some(body.clone());
+            resp = HttpResponse::from_bytes(StatusCode::OK, headers, body);
+        }
+        let result = self.handle_json_response(&req_url, resp).await;
+        if let Some(object_cache) = object_cache {
+            if !is_get {
+                // Dropped once the change has been sent so a read made while it was in flight
+                // doesn't stay cached.  The server may have applied it even if it responded with
+                // an error.
+                object_cache.invalidate(req_url.as_str());
+            } else if let (Some(body), Ok(_)) = (cacheable, &result) {
+                object_cache.put(&req_url, body);
+            }
+        }
+        result
+    }
+
+    // Waits for a free slot if the number of requests in flight is limited
+    async fn acquire(limit: Option<&Arc<Semaphore>>) -> Option<OwnedSemaphorePermit> {
+        match limit {
+            Some(limit) => Some(
+                limit
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("Concurrency limit is never closed"),
+            ),
+            None => None,
+        }
+    }
+
+    // Keeps the slot taken until the body has been dropped
+    fn hold_permit(body: HttpBodyStream, permit: Option<OwnedSemaphorePermit>) -> HttpBodyStream {
+        match permit {
+            Some(permit) => Box::pin(body.map(move |chunk| {
+                let _permit = &permit;
+                chunk
+            })),
+            None => body,
+        }
+    }
+
+    // Serves the cached body for a 304 response, otherwise caches the successful response if it
+    // can be revalidated or the cache is a snapshot
+    async fn apply_http_cache(
+        http_cache: &dyn HttpCache,
+        cache_key: &str,
+        cached: Option<CachedResponse>,
+        resp: HttpResponse,
+    ) -> Result<HttpResponse, SmugMugError> {
+        let status = resp.status();
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(match cached {
+                Some(cached) => {
+                    HttpResponse::from_bytes(StatusCode::OK, resp.headers().clone(), cached.body)
+                }
+                None => resp,
+            });
+        }
+        if status != StatusCode::OK {
+            return Ok(resp);
+        }
+
+        let headers = resp.headers().clone();
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        if etag.is_none() && last_modified.is_none() && !http_cache.is_snapshot() {
+            return Ok(resp);
+        }
+
+        let body = resp.bytes().await?;
+        http_cache.put(
+            cache_key,
+            CachedResponse {
+                etag,
+                last_modified,
+                body: body.clone(),
+            },
+        );
+        Ok(HttpResponse::from_bytes(status, headers, body))
     }
 
-    // Performs a POST request to the SmugMug API
-    async fn post<T: DeserializeOwned>(
+    // Signs and sends the request within a span covering the API call
+    async fn send(
         &self,
-        url: &str,
-        data: Vec<u8>,
-        params: Option<&ApiParams<'_>>,
-    ) -> Result<Response<T>, SmugMugError> {
-        let req_url = self.create_req(url, params)?;
-        let auth_header = self.creds.create_oauth1_header("POST", &req_url)?;
-        let resp = self
-            .https_client
-            .clone()
-            .post(req_url)
-            .header("Accept", "application/json")
-            .header("Content-Type", "application/json")
-            .header("Authorization", auth_header)
-            .body(data)
-            .send()
-            .await?;
-        self.handle_json_response(resp).await
+        method: Method,
+        req_url: &url::Url,
+        accept_json: bool,
+        headers: HeaderMap,
+        data: Option<Bytes>,
+    ) -> Result<HttpResponse, SmugMugError> {
+        if self.offline {
+            return Err(if method == Method::GET && accept_json {
+                SmugMugError::OfflineCacheMiss(redact_url(req_url))
+            } else {
+                SmugMugError::OfflineUnsupported(method.to_string(), redact_url(req_url))
+            });
+        }
+
+        let span = CallSpan::new(&method, req_url);
+        let result = span
+            .instrument(self.send_with_retries(method, req_url, accept_json, headers, data))
+            .await;
+        span.record(&result);
+        result
+    }
+
+    // Sends the request, retrying per the retry policy if one is configured
+    async fn send_with_retries(
+        &self,
+        method: Method,
+        req_url: &url::Url,
+        accept_json: bool,
+        headers: HeaderMap,
+        data: Option<Bytes>,
+    ) -> Result<HttpResponse, SmugMugError> {
+        let endpoint = endpoint_template(req_url);
+        let mut attempt: u32 = 1;
+        loop {
+            self.throttle().await;
+            let mut req = self.build_request(
+                method.clone(),
+                req_url,
+                accept_json,
+                headers.clone(),
+                data.clone(),
+            )?;
+            for interceptor in &self.interceptors {
+                interceptor.on_request(&mut req)?;
+            }
+
+            let started = std::time::Instant::now();
+            let result = self.transport.send(req).await;
+            self.metrics.record_request(
+                &method,
+                endpoint.clone(),
+                result.as_ref().ok().map(|v| v.status()),
+                started.elapsed(),
+            );
+            let result = match result {
+                Ok(resp) => {
+                    let mut resp = resp.map_body(|v| self.metrics.count_body(v));
+                    for interceptor in &self.interceptors {
+                        interceptor.on_response(&mut resp)?;
+                    }
+                    Ok(resp)
+                }
+                Err(err) => Err(err),
+            };
+
+            let Some(retry_policy) = self
+                .retry_policy
+                .as_ref()
+                .filter(|v| attempt < v.max_attempts())
+            else {
+                return result;
+            };
+
+            let backoff = match result {
+                Ok(resp) if retry_policy.is_retryable_status(&method, resp.status()) => {
+                    // Honor the servers retry after if it was given
+                    RateLimitWindow::from_headers(Utc::now(), resp.headers())
+                        .retry_after_seconds()
+                        .map(std::time::Duration::from_secs)
+                        .unwrap_or_else(|| retry_policy.backoff(attempt))
+                }
+                Err(err) if retry_policy.is_retryable_error(&method, &err) => {
+                    retry_policy.backoff(attempt)
+                }
+                result => return result,
+            };
+
+            log::debug!(
+                "Retrying {} request attempt: {} after: {:?}",
+                method,
+                attempt + 1,
+                backoff
+            );
+            self.metrics.record_retry();
+            self.transport.sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    // Waits if needed so the rate limit window isn't exceeded
+    async fn throttle(&self) {
+        let Some(throttle_policy) = self.throttle_policy.as_ref() else {
+            return;
+        };
+        let Some(rate_window) = self.last_rate_window.load() else {
+            return;
+        };
+        if let Some(delay) = throttle_policy.delay_for(&rate_window, Utc::now()) {
+            log::debug!(
+                "Throttling request for: {:?} remaining requests: {:?}",
+                delay,
+                rate_window.num_remaining_requests()
+            );
+            self.transport.sleep(delay).await;
+        }
+    }
+
+    // Builds the request with the client wide settings applied, signing it with OAuth1 if needed
+    fn build_request(
+        &self,
+        method: Method,
+        req_url: &url::Url,
+        accept_json: bool,
+        headers: HeaderMap,
+        data: Option<Bytes>,
+    ) -> Result<HttpRequest, SmugMugError> {
+        let mut req = HttpRequest::new(method.clone(), req_url.clone());
+        req.headers = headers;
+        req.timeout = self.timeout;
+        if let Some(user_agent) = self.user_agent.as_ref() {
+            req.headers.insert(USER_AGENT, user_agent.clone());
+        }
+        if accept_json {
+            req.headers
+                .insert(ACCEPT, HeaderValue::from_static("application/json"));
+        }
+
+        // If we are in read-only mode we have to do this a little different.  Since other functions
+        // require Oauth1 singing, this is only needed for get.
+        if method != Method::GET || self.creds.are_all_tokens_available() {
+            let auth_header = self.creds.create_oauth1_header(method.as_str(), req_url)?;
+            req.headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&auth_header)
+                    .map_err(|_| SmugMugError::Auth("Invalid authorization header".to_string()))?,
+            );
+        }
+
+        if let Some(data) = data {
+            req.headers
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            req.body = Some(data);
+        }
+        Ok(req)
     }
 
     // Parse the rate limit headers that are returned.
-    fn extract_rate_limits_from_response(&self, resp: &ReqwestResponse) -> Arc<RateLimitWindow> {
+    fn extract_rate_limits_from_response(&self, resp: &HttpResponse) -> Arc<RateLimitWindow> {
         // Extract the rate limits
-        let rate_limit = Arc::new(RateLimitWindow::from_reqwest_headers(
-            Utc::now(),
-            resp.headers(),
-        ));
+        let rate_limit = Arc::new(RateLimitWindow::from_headers(Utc::now(), resp.headers()));
 
         // Write the latest value to the clients stores last rate window update area
-        self.last_rate_window
-            .write()
-            .map(|mut v| {
-                *v = rate_limit.clone();
-            })
-            .unwrap();
+        if rate_limit.is_valid() {
+            self.last_rate_window.store(rate_limit.clone());
+        }
         rate_limit
     }
 
     // Returns an error on an http error
     fn error_on_http_status(
         &self,
-        resp: &ReqwestResponse,
+        req_url: &url::Url,
+        resp: &HttpResponse,
         rate_limit: Option<&RateLimitWindow>,
     ) -> Result<(), SmugMugError> {
-        let _ = resp.error_for_status_ref().map_err(|v| {
-            match rate_limit.and_then(|v| v.retry_after_seconds()) {
-                Some(retry_after) if resp.status().as_u16() == 429 => {
-                    SmugMugError::ApiResponseTooManyRequests(retry_after)
-                }
-                _ => SmugMugError::from(v),
+        let status = resp.status();
+        if !status.is_client_error() && !status.is_server_error() {
+            return Ok(());
+        }
+        match rate_limit.and_then(|v| v.retry_after_seconds()) {
+            Some(retry_after) if status == StatusCode::TOO_MANY_REQUESTS => {
+                Err(SmugMugError::ApiResponseTooManyRequests(retry_after))
             }
-        })?;
-        Ok(())
+            _ => Err(SmugMugError::HttpStatus(
+                status.as_u16(),
+                redact_url(req_url),
+            )),
+        }
     }
 
     // Response handling logic
     async fn handle_json_response<T: DeserializeOwned>(
         &self,
-        resp: ReqwestResponse,
+        req_url: &url::Url,
+        resp: HttpResponse,
     ) -> Result<Response<T>, SmugMugError> {
         // Get current rate limit values
         let rate_limit = self.extract_rate_limits_from_response(&resp);
 
         // Check if the http error code returned was an error
-        self.error_on_http_status(&resp, Some(&rate_limit))?;
+        self.error_on_http_status(req_url, &resp, Some(&rate_limit))?;
 
         // get the payload bytes
         let payload_bytes = resp.bytes().await?;
 
-        if log::log_enabled!(log::Level::Debug) &&
-            let Ok(val) = serde_json::from_slice::<serde_json::Value>(&payload_bytes) {
+        if log::log_enabled!(log::Level::Debug)
+            && let Ok(val) = serde_json::from_slice::<serde_json::Value>(&payload_bytes)
+        {
             log::debug!("JSON Raw Resp: {}", serde_json::to_string_pretty(&val)?);
         }
 
         // Pull out the payload
+        Ok(Response {
+            payload: Self::parse_json_payload(&payload_bytes)?,
+            rate_limit: Some(rate_limit),
+        })
+    }
+
+    // Parses the payload out of the JSON response body
+    fn parse_json_payload<T: DeserializeOwned>(
+        payload_bytes: &Bytes,
+    ) -> Result<Option<T>, SmugMugError> {
         match serde_json::from_slice::<ResponseBody<T>>(payload_bytes.as_ref()) {
             Ok(body) => {
                 if !body.is_code_an_error()? {
                     return Err(SmugMugError::ApiResponse(body.code, body.message));
                 }
-                Ok(Response {
-                    payload: body.response,
-                    rate_limit: Some(rate_limit),
-                })
+                Ok(body.response)
             }
             Err(err) => {
                 if log::log_enabled!(log::Level::Debug) {
@@ -311,19 +1270,19 @@ impl ClientRef {
         &self,
         url: &str,
         params: Option<&ApiParams<'_>>,
-    ) -> Result<reqwest::Url, SmugMugError> {
-        let mut req_url = params.map_or(reqwest::Url::parse(url), |v| {
-            reqwest::Url::parse_with_params(url, v)
+    ) -> Result<url::Url, SmugMugError> {
+        let mut req_url = params.map_or(url::Url::parse(url), |v| {
+            url::Url::parse_with_params(url, v)
         })?;
 
         if self.creds.access_token.is_none() || self.creds.token_secret.is_none() {
-            req_url = reqwest::Url::parse_with_params(
+            req_url = url::Url::parse_with_params(
                 req_url.as_str(),
                 [("APIKey", &self.creds.consumer_api_key)],
             )?;
         }
         if log::log_enabled!(log::Level::Trace) {
-            log::trace!("Outgoing request url: {}", req_url.as_str());
+            log::trace!("Outgoing request url: {}", redact_url(&req_url));
         }
         Ok(req_url)
     }
@@ -331,7 +1290,10 @@ impl ClientRef {
 
 impl std::fmt::Debug for Client {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ApiClient").finish()
+        f.debug_struct("ApiClient")
+            .field("api", &self.api.as_ref().map(|_| "SmugMugApi"))
+            .field("budget", &self.budget)
+            .finish()
     }
 }
 
@@ -364,7 +1326,7 @@ pub enum ApiErrorCodes {
 }
 
 /// The call rate limits returned from the REST API call.
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct RateLimitWindow {
     num_remaining_requests: Option<u64>,
     current_window_reset_datetime: Option<DateTime<Utc>>,
@@ -374,7 +1336,7 @@ pub struct RateLimitWindow {
 
 impl RateLimitWindow {
     // Pull out the rate limit headers
-    fn from_reqwest_headers(timestamp: DateTime<Utc>, headers: &HeaderMap) -> Self {
+    pub(crate) fn from_headers(timestamp: DateTime<Utc>, headers: &HeaderMap) -> Self {
         // Parse the headers fully per call since we are rate limited and taking time to parse
         // is potentially better than storing raw and performance of parsing on call.
         let retry_after_seconds = headers
@@ -435,14 +1397,34 @@ impl RateLimitWindow {
     }
 }
 
-/// Response returned by the [`Client`]` get/get_binary_data/post/patch/requests
+/// Response returned by the [`Client`]` get/get_binary_data/post/patch/put/delete/requests
 pub struct Response<T> {
     /// Request data being returned.
     pub payload: Option<T>,
-    /// Returns rate limit for the last call.  This will be None for binary data retrieval.
+    /// Returns rate limit for the last call.
     pub rate_limit: Option<Arc<RateLimitWindow>>,
 }
 
+// Member of the `Response` holding the object, which is the one named by the `Locator` or else
+// the first object member
+fn response_object_key(response: &serde_json::Value) -> Option<String> {
+    let members = response.as_object()?;
+    match members.get("Locator").and_then(|v| v.as_str()) {
+        Some(locator) => Some(locator.to_string()),
+        None => members
+            .iter()
+            .find(|(_, v)| v.is_object())
+            .map(|(key, _)| key.clone()),
+    }
+}
+
+// Object held in the `Response` member of a reply
+pub(crate) fn response_object(
+    response: &serde_json::Value,
+) -> Option<&serde_json::Map<String, serde_json::Value>> {
+    response.get(response_object_key(response)?)?.as_object()
+}
+
 /// Holds credentials used for accessing/signing REST requests
 #[derive(Default, Clone)]
 pub struct Creds {
@@ -476,11 +1458,7 @@ impl Creds {
             && self.token_secret.is_some()
     }
 
-    fn create_oauth1_header(
-        &self,
-        method: &str,
-        url: &reqwest::Url,
-    ) -> Result<String, SmugMugError> {
+    fn create_oauth1_header(&self, method: &str, url: &url::Url) -> Result<String, SmugMugError> {
         let access_token = self
             .access_token
             .as_ref()
@@ -0,0 +1,261 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::Client;
+use crate::v2::cancel::CancellationToken;
+use crate::v2::errors::SmugMugError;
+use crate::v2::transport::HttpBodyStream;
+use bytes::Bytes;
+use futures::{Stream, TryStreamExt};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+/// Summary of a completed download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadSummary {
+    /// Number of bytes written
+    pub size: u64,
+    /// Lower case hex MD5 digest of the bytes written
+    pub md5: String,
+}
+
+/// Result of a download that can be cancelled with a [`CancellationToken`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadOutcome {
+    /// All of the data was downloaded
+    Completed(DownloadSummary),
+    /// The download was cancelled.  The summary covers the bytes written before then, so its
+    /// `size` is the byte offset to resume from.
+    Cancelled(DownloadSummary),
+}
+
+impl DownloadOutcome {
+    /// Whether the download was cancelled before completing
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled(_))
+    }
+
+    /// Summary of the bytes written whether or not the download completed
+    pub fn into_summary(self) -> DownloadSummary {
+        match self {
+            Self::Completed(summary) | Self::Cancelled(summary) => summary,
+        }
+    }
+}
+
+/// Body of a response to a request for binary data starting at an offset
+pub struct RangedBody {
+    /// Byte offset the body starts at
+    pub offset: u64,
+    /// Stream of body chunks
+    pub body: HttpBodyStream,
+}
+
+impl std::fmt::Debug for RangedBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RangedBody")
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+/// Expected size and MD5 of the data being downloaded
+#[derive(Debug)]
+pub(crate) struct ExpectedDownload<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) size: Option<u64>,
+    pub(crate) md5: Option<&'a str>,
+}
+
+// Writes the stream into the writer computing the MD5 along the way, stopping early if the
+// token is cancelled
+pub(crate) async fn write_stream<S, W>(
+    stream: S,
+    writer: &mut W,
+    cancel: Option<&CancellationToken>,
+) -> Result<DownloadOutcome, SmugMugError>
+where
+    S: Stream<Item = Result<Bytes, SmugMugError>>,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let (md5_ctx, size, cancelled) =
+        append_stream(stream, writer, md5::Context::new(), 0, cancel).await?;
+    Ok(outcome(md5_ctx, size, cancelled))
+}
+
+// Downloads the url to the path using a `.part` file next to it.  If the partial file exists
+// from an earlier attempt only the remainder is requested.  The result is checked against the
+// expected size and MD5 before being moved into place.  If the token is cancelled the partial
+// file is kept for the next attempt.
+pub(crate) async fn download_resumable(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    expected: ExpectedDownload<'_>,
+    cancel: Option<&CancellationToken>,
+) -> Result<DownloadOutcome, SmugMugError> {
+    let part_path = part_path(path);
+    let mut file = tokio::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&part_path)
+        .await?;
+
+    // Pick up from whatever made it to disk last time
+    let mut existing = file.metadata().await?.len();
+    if expected.size.is_some_and(|size| existing > size) {
+        existing = 0;
+    }
+    let (mut md5_ctx, mut size) = hash_prefix(&mut file, existing).await?;
+
+    if expected
+        .size
+        .is_none_or(|expected_size| size < expected_size)
+    {
+        let request = client.get_binary_stream_from(url, None, size);
+        let resp = match cancel {
+            Some(cancel) => match cancel.run_until_cancelled(request).await {
+                Some(resp) => resp,
+                None => return Ok(outcome(md5_ctx, size, true)),
+            },
+            None => request.await,
+        };
+        let ranged = resp?.payload.ok_or(SmugMugError::ResponseMissing())?;
+        if ranged.offset != size {
+            if ranged.offset != 0 {
+                return Err(SmugMugError::ArchiveVerificationFailed(
+                    expected.name.to_string(),
+                    format!("requested offset {size} but got {}", ranged.offset),
+                ));
+            }
+            // Range was ignored so start over
+            md5_ctx = md5::Context::new();
+            size = 0;
+        }
+        file.set_len(size).await?;
+        file.seek(SeekFrom::Start(size)).await?;
+        let cancelled;
+        (md5_ctx, size, cancelled) =
+            append_stream(ranged.body, &mut file, md5_ctx, size, cancel).await?;
+        if cancelled {
+            return Ok(outcome(md5_ctx, size, true));
+        }
+    }
+    drop(file);
+
+    let summary = DownloadSummary {
+        size,
+        md5: format!("{:x}", md5_ctx.finalize()),
+    };
+    if let Err(err) = verify(&summary, &expected) {
+        // The partial data can't be trusted so don't resume from it next time
+        tokio::fs::remove_file(&part_path).await?;
+        return Err(err);
+    }
+    tokio::fs::rename(&part_path, path).await?;
+    Ok(DownloadOutcome::Completed(summary))
+}
+
+// Summarises the data written so far
+pub(crate) fn outcome(md5_ctx: md5::Context, size: u64, cancelled: bool) -> DownloadOutcome {
+    let summary = DownloadSummary {
+        size,
+        md5: format!("{:x}", md5_ctx.finalize()),
+    };
+    if cancelled {
+        DownloadOutcome::Cancelled(summary)
+    } else {
+        DownloadOutcome::Completed(summary)
+    }
+}
+
+// Path of the partial file used while downloading to the given path
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+// Writes the stream after the already written data, continuing the MD5 and size.  Also returns
+// whether the token was cancelled before the end of the stream.
+async fn append_stream<S, W>(
+    stream: S,
+    writer: &mut W,
+    mut md5_ctx: md5::Context,
+    mut size: u64,
+    cancel: Option<&CancellationToken>,
+) -> Result<(md5::Context, u64, bool), SmugMugError>
+where
+    S: Stream<Item = Result<Bytes, SmugMugError>>,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    futures::pin_mut!(stream);
+    let mut cancelled = false;
+    loop {
+        let next = match cancel {
+            Some(cancel) => match cancel.run_until_cancelled(stream.try_next()).await {
+                Some(next) => next?,
+                None => {
+                    cancelled = true;
+                    break;
+                }
+            },
+            None => stream.try_next().await?,
+        };
+        let Some(chunk) = next else {
+            break;
+        };
+        md5_ctx.consume(&chunk);
+        size += chunk.len() as u64;
+        writer.write_all(&chunk).await?;
+    }
+    writer.flush().await?;
+    Ok((md5_ctx, size, cancelled))
+}
+
+// Computes the MD5 of the first `len` bytes of the file
+async fn hash_prefix(
+    file: &mut tokio::fs::File,
+    len: u64,
+) -> Result<(md5::Context, u64), SmugMugError> {
+    let mut md5_ctx = md5::Context::new();
+    let mut reader = (&mut *file).take(len);
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut size: u64 = 0;
+    loop {
+        let read = reader.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        md5_ctx.consume(&buf[..read]);
+        size += read as u64;
+    }
+    Ok((md5_ctx, size))
+}
+
+// Checks the downloaded data matches what was expected
+fn verify(summary: &DownloadSummary, expected: &ExpectedDownload<'_>) -> Result<(), SmugMugError> {
+    if let Some(size) = expected.size.filter(|v| *v != summary.size) {
+        return Err(SmugMugError::ArchiveVerificationFailed(
+            expected.name.to_string(),
+            format!("expected {size} bytes but got {}", summary.size),
+        ));
+    }
+    if let Some(md5) = expected
+        .md5
+        .filter(|v| !v.eq_ignore_ascii_case(&summary.md5))
+    {
+        return Err(SmugMugError::ArchiveVerificationFailed(
+            expected.name.to_string(),
+            format!("expected MD5 {md5} but got {}", summary.md5),
+        ));
+    }
+    Ok(())
+}
@@ -55,4 +55,31 @@ pub enum SmugMugError {
 
     #[error("Failed serializing to JSON: {0}")]
     JsonSerialization(String),
+
+    #[error("HTTP status error: {0} for url: {1}")]
+    HttpStatus(u16, String),
+
+    #[error("Archive verification failed for: {0}. {1}")]
+    ArchiveVerificationFailed(String, String),
+
+    #[error("Offline and no cached response for: {0}")]
+    OfflineCacheMiss(String),
+
+    #[error("Offline and {0} requests are never cached for: {1}")]
+    OfflineUnsupported(String, String),
+
+    #[error("No recorded response in cassette for: {0}")]
+    CassetteMismatch(String),
+
+    #[error("Journal entry {0} can't be undone since {1}")]
+    JournalUndo(u64, String),
+
+    #[error("Undo stopped after reverting {0} mutations: {1}")]
+    UndoIncomplete(usize, #[source] Box<SmugMugError>),
+
+    #[error("Request budget of {0} API calls exhausted")]
+    BudgetExhausted(u64),
+
+    #[error("Invalid client configuration: {0}")]
+    InvalidConfig(String),
 }
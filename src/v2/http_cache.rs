@@ -0,0 +1,269 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use base64::prelude::*;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Body of a GET response along with any validators used for revalidating it
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// Value of the `ETag` header sent back as `If-None-Match`
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` header sent back as `If-Modified-Since`
+    pub last_modified: Option<String>,
+    /// Response body served when the server replies with `304 Not Modified`
+    pub body: Bytes,
+}
+
+/// Cache used by the [`crate::v2::Client`] for conditional GET requests.
+///
+/// Responses carrying an `ETag` or `Last-Modified` header are stored keyed by the request url,
+/// with any credentials in the query replaced.  Later requests for the same url send the
+/// validators and if the server replies with a `304 Not Modified` the cached body is used instead.
+/// Snapshot caches (see [`HttpCache::is_snapshot`]) store every successful response.  The cache is
+/// enabled with [`crate::v2::ClientBuilder::http_cache`].
+///
+/// When the client is in offline mode (see [`crate::v2::ClientBuilder::offline`]) the cached
+/// responses are served without making any requests.
+///
+/// ```rust
+/// use smugmug::v2::{Client, InMemoryHttpCache};
+/// use std::sync::Arc;
+///
+/// let client = Client::builder()
+///     .http_cache(Arc::new(InMemoryHttpCache::default()))
+///     .build()
+///     .unwrap();
+/// ```
+pub trait HttpCache: Send + Sync {
+    /// Returns the cached response for the url if there is one
+    fn get(&self, url: &str) -> Option<CachedResponse>;
+
+    /// Stores the response for the url replacing any earlier one
+    fn put(&self, url: &str, response: CachedResponse);
+
+    /// Returns true if successful responses without validators should be stored as well so they
+    /// can be served in offline mode.  Defaults to false as such responses can't be revalidated.
+    fn is_snapshot(&self) -> bool {
+        false
+    }
+}
+
+/// [`HttpCache`] holding up to a fixed number of responses in memory.  Once full the least
+/// recently used response is evicted to make room.
+#[derive(Debug)]
+pub struct InMemoryHttpCache {
+    capacity: usize,
+    responses: Mutex<CachedResponses>,
+}
+
+#[derive(Debug, Default)]
+struct CachedResponses {
+    responses: HashMap<String, CachedEntry>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct CachedEntry {
+    response: CachedResponse,
+    last_used: u64,
+}
+
+impl Default for InMemoryHttpCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl InMemoryHttpCache {
+    /// Number of responses held by a cache created with [`InMemoryHttpCache::default`]
+    pub const DEFAULT_CAPACITY: usize = 1000;
+
+    /// Creates a cache holding up to `capacity` responses
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            responses: Mutex::new(CachedResponses::default()),
+        }
+    }
+
+    /// Maximum number of responses held
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cached responses
+    pub fn len(&self) -> usize {
+        self.lock().responses.len()
+    }
+
+    /// Returns true if nothing has been cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached responses
+    pub fn clear(&self) {
+        self.lock().responses.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CachedResponses> {
+        self.responses
+            .lock()
+            .expect("Failed locking the http cache")
+    }
+}
+
+impl HttpCache for InMemoryHttpCache {
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        let mut responses = self.lock();
+        responses.tick += 1;
+        let tick = responses.tick;
+        let entry = responses.responses.get_mut(url)?;
+        entry.last_used = tick;
+        Some(entry.response.clone())
+    }
+
+    // Stores the response evicting the least recently used one if full
+    fn put(&self, url: &str, response: CachedResponse) {
+        let mut responses = self.lock();
+        responses.tick += 1;
+        let tick = responses.tick;
+        if responses.responses.len() >= self.capacity && !responses.responses.contains_key(url) {
+            let lru = responses
+                .responses
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                responses.responses.remove(&lru);
+            }
+        }
+        responses.responses.insert(
+            url.to_string(),
+            CachedEntry {
+                response,
+                last_used: tick,
+            },
+        );
+    }
+}
+
+/// [`HttpCache`] storing each response as a file in a directory so it survives restarts.
+///
+/// Every successful response is stored, including those without validators, so the directory
+/// holds a snapshot of what was read.  The files are named after and hold the request url with any
+/// credentials in the query replaced.
+///
+/// Together with [`crate::v2::ClientBuilder::offline`] this allows running read-only reports
+/// against a snapshot of earlier responses without connectivity.  Failures reading or writing the
+/// files are logged and treated as cache misses.
+///
+/// ```rust,no_run
+/// use smugmug::v2::{Client, DiskHttpCache};
+/// use std::sync::Arc;
+///
+/// // Take the snapshot while online
+/// let cache = Arc::new(DiskHttpCache::new("smugmug-cache"));
+/// let client = Client::builder().http_cache(cache.clone()).build().unwrap();
+///
+/// // Later serve everything from the snapshot
+/// let offline_client = Client::builder()
+///     .http_cache(cache)
+///     .offline(true)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiskHttpCache {
+    dir: PathBuf,
+}
+
+// Format of the files written by the disk cache
+#[derive(Serialize, Deserialize)]
+struct DiskCacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+impl DiskHttpCache {
+    /// Creates a cache storing its files in the given directory.  The directory is created when
+    /// the first response is stored.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    // File holding the response for the url
+    fn entry_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:x}.json", md5::compute(url)))
+    }
+
+    fn try_get(&self, url: &str) -> std::io::Result<Option<CachedResponse>> {
+        let contents = match std::fs::read(self.entry_path(url)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let entry: DiskCacheEntry = serde_json::from_slice(&contents)?;
+        if entry.url != url {
+            return Ok(None);
+        }
+        let body = BASE64_STANDARD
+            .decode(entry.body)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        Ok(Some(CachedResponse {
+            etag: entry.etag,
+            last_modified: entry.last_modified,
+            body: body.into(),
+        }))
+    }
+
+    fn try_put(&self, url: &str, response: &CachedResponse) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let entry = DiskCacheEntry {
+            url: url.to_string(),
+            etag: response.etag.clone(),
+            last_modified: response.last_modified.clone(),
+            body: BASE64_STANDARD.encode(&response.body),
+        };
+
+        // Write to a temporary file first so readers never see a partial entry
+        let path = self.entry_path(url);
+        let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&tmp_path, serde_json::to_vec(&entry)?)?;
+        std::fs::rename(&tmp_path, &path)
+    }
+}
+
+impl HttpCache for DiskHttpCache {
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        self.try_get(url)
+            .inspect_err(|err| {
+                log::warn!("Failed reading http cache entry in {:?}: {err}", self.dir);
+            })
+            .ok()
+            .flatten()
+    }
+
+    fn put(&self, url: &str, response: CachedResponse) {
+        if let Err(err) = self.try_put(url, &response) {
+            log::warn!("Failed writing http cache entry in {:?}: {err}", self.dir);
+        }
+    }
+
+    fn is_snapshot(&self) -> bool {
+        true
+    }
+}
@@ -5,16 +5,23 @@
  *  - MIT license <http://opensource.org/licenses/MIT>
  *  at your option.
  */
+use crate::v2::Client;
+use crate::v2::cancel::CancellationToken;
+use crate::v2::download::{
+    DownloadOutcome, DownloadSummary, ExpectedDownload, download_resumable, outcome, write_stream,
+};
 use crate::v2::errors::SmugMugError;
 use crate::v2::macros::{
     obj_from_url, obj_update_from_uri, obj_update_from_url, objs_from_id_slice,
 };
-use crate::v2::{Client, API_ORIGIN};
+use crate::v2::transport::HttpBodyStream;
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
+use std::path::Path;
+use tokio::io::AsyncWrite;
 
 /// Holds information returned from the AlbumImage/Image API.
 ///
@@ -94,9 +101,7 @@ impl Image {
 
     /// Returns information for the specified image id
     pub async fn from_id(client: Client, id: &str) -> Result<Self, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join(Self::BASE_URI)?
-            .join(id)?;
+        let req_url = client.api_url(Self::BASE_URI)?.join(id)?;
         Self::from_url(client, req_url.as_str()).await
     }
 
@@ -123,20 +128,18 @@ impl Image {
         data: Vec<u8>,
         id: &str,
     ) -> Result<Image, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join(Self::BASE_URI)?
-            .join(id)?;
+        let req_url = client.api_url(Self::BASE_URI)?.join(id)?;
         obj_update_from_url!(client, req_url.as_str(), data, ImageResponse, image)
     }
 
     /// Retrieves the image data found at the archive uri
     pub async fn get_archive_with_client(&self, client: Client) -> Result<Bytes, SmugMugError> {
         match self.archived_uri.as_ref() {
-            Some(archived_uri) => Ok(client
+            Some(archived_uri) => client
                 .get_binary_data(archived_uri, None)
                 .await?
                 .payload
-                .unwrap()),
+                .ok_or(SmugMugError::ResponseMissing()),
             None => Err(SmugMugError::ImageArchiveNotFound(
                 self.file_name.clone(),
                 self.image_key.clone(),
@@ -145,15 +148,175 @@ impl Image {
     }
 
     pub async fn get_archive(&self) -> Result<Bytes, SmugMugError> {
-        self.get_archive_with_client(
-            self.client
-                .as_ref()
-                .ok_or(SmugMugError::ClientNotFound())
-                .unwrap()
-                .clone(),
-        )
+        self.get_archive_with_client(self.client()?).await
+    }
+
+    /// Retrieves the image data found at the archive uri as a stream of chunks.  This avoids
+    /// holding the whole file in memory for large images and videos.
+    pub async fn get_archive_stream_with_client(
+        &self,
+        client: Client,
+    ) -> Result<HttpBodyStream, SmugMugError> {
+        match self.archived_uri.as_ref() {
+            Some(archived_uri) => client
+                .get_binary_stream(archived_uri, None)
+                .await?
+                .payload
+                .ok_or(SmugMugError::ResponseMissing()),
+            None => Err(SmugMugError::ImageArchiveNotFound(
+                self.file_name.clone(),
+                self.image_key.clone(),
+            )),
+        }
+    }
+
+    /// Retrieves the image data found at the archive uri as a stream of chunks
+    pub async fn get_archive_stream(&self) -> Result<HttpBodyStream, SmugMugError> {
+        self.get_archive_stream_with_client(self.client()?).await
+    }
+
+    /// Writes the image data found at the archive uri to the writer, returning the number of
+    /// bytes written and their MD5 which can be compared with `archived_size` and `archived_md5`
+    pub async fn download_archive_to_with_client<W>(
+        &self,
+        client: Client,
+        writer: &mut W,
+    ) -> Result<DownloadSummary, SmugMugError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let stream = self.get_archive_stream_with_client(client).await?;
+        write_stream(stream, writer, None)
+            .await
+            .map(DownloadOutcome::into_summary)
+    }
+
+    /// Writes the image data found at the archive uri to the writer
+    pub async fn download_archive_to<W>(
+        &self,
+        writer: &mut W,
+    ) -> Result<DownloadSummary, SmugMugError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        self.download_archive_to_with_client(self.client()?, writer)
+            .await
+    }
+
+    /// Writes the image data found at the archive uri to the writer using the provided client,
+    /// stopping once the token is cancelled.  A cancelled download reports the bytes written
+    /// before then.
+    pub async fn download_archive_to_cancellable_with_client<W>(
+        &self,
+        client: Client,
+        writer: &mut W,
+        cancel: &CancellationToken,
+    ) -> Result<DownloadOutcome, SmugMugError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let Some(stream) = cancel
+            .run_until_cancelled(self.get_archive_stream_with_client(client))
+            .await
+        else {
+            return Ok(outcome(md5::Context::new(), 0, true));
+        };
+        write_stream(stream?, writer, Some(cancel)).await
+    }
+
+    /// Writes the image data found at the archive uri to the writer, stopping once the token is
+    /// cancelled
+    pub async fn download_archive_to_cancellable<W>(
+        &self,
+        writer: &mut W,
+        cancel: &CancellationToken,
+    ) -> Result<DownloadOutcome, SmugMugError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        self.download_archive_to_cancellable_with_client(self.client()?, writer, cancel)
+            .await
+    }
+
+    /// Writes the image data found at the archive uri to the file at the given path, replacing
+    /// it if it exists
+    pub async fn download_archive_to_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadSummary, SmugMugError> {
+        let mut file = tokio::fs::File::create(path).await?;
+        self.download_archive_to(&mut file).await
+    }
+
+    /// Downloads the image data found at the archive uri to the file at the given path.  The data
+    /// is first written to a `.part` file next to it so an interrupted download can be picked up
+    /// again by calling this with the same path, which only requests the remaining bytes.  The
+    /// result is checked against `archived_size` and `archived_md5` before being moved into place.
+    pub async fn download_archive_resumable_with_client(
+        &self,
+        client: Client,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadSummary, SmugMugError> {
+        self.download_resumable(client, path.as_ref(), None)
+            .await
+            .map(DownloadOutcome::into_summary)
+    }
+
+    /// Downloads the image data found at the archive uri to the file at the given path, resuming
+    /// an earlier interrupted download
+    pub async fn download_archive_resumable(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadSummary, SmugMugError> {
+        self.download_archive_resumable_with_client(self.client()?, path)
             .await
     }
+
+    /// Downloads the image data found at the archive uri to the file at the given path using the
+    /// provided client, stopping once the token is cancelled.  A cancelled download leaves its
+    /// `.part` file in place so calling this again with the same path continues from the offset
+    /// reported in [`DownloadOutcome::Cancelled`].
+    pub async fn download_archive_resumable_cancellable_with_client(
+        &self,
+        client: Client,
+        path: impl AsRef<Path>,
+        cancel: &CancellationToken,
+    ) -> Result<DownloadOutcome, SmugMugError> {
+        self.download_resumable(client, path.as_ref(), Some(cancel))
+            .await
+    }
+
+    /// Downloads the image data found at the archive uri to the file at the given path, resuming
+    /// an earlier interrupted download and stopping once the token is cancelled
+    pub async fn download_archive_resumable_cancellable(
+        &self,
+        path: impl AsRef<Path>,
+        cancel: &CancellationToken,
+    ) -> Result<DownloadOutcome, SmugMugError> {
+        self.download_archive_resumable_cancellable_with_client(self.client()?, path, cancel)
+            .await
+    }
+
+    async fn download_resumable(
+        &self,
+        client: Client,
+        path: &Path,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<DownloadOutcome, SmugMugError> {
+        let archived_uri = self.archived_uri.as_ref().ok_or_else(|| {
+            SmugMugError::ImageArchiveNotFound(self.file_name.clone(), self.image_key.clone())
+        })?;
+        let expected = ExpectedDownload {
+            name: &self.file_name,
+            size: self.archived_size,
+            md5: self.archived_md5.as_deref(),
+        };
+        download_resumable(&client, archived_uri, path, expected, cancel).await
+    }
+
+    fn client(&self) -> Result<Client, SmugMugError> {
+        self.client.clone().ok_or(SmugMugError::ClientNotFound())
+    }
 }
 
 impl PartialEq for Image {
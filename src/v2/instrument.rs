@@ -0,0 +1,149 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::errors::SmugMugError;
+use crate::v2::transport::HttpResponse;
+use http::Method;
+use std::future::Future;
+
+const API_PATH_PREFIX: &str = "/api/v2/";
+
+const REDACTED: &str = "REDACTED";
+
+// Query parameters holding credentials that are never logged, traced or recorded
+const REDACTED_PARAMS: [&str; 2] = ["APIKey", "oauth_"];
+
+// Replaces the credentials in the query parameters of the url
+pub(crate) fn redact_url(url: &url::Url) -> String {
+    let mut redacted = url.clone();
+    let params: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let is_secret = REDACTED_PARAMS.iter().any(|v| name.starts_with(v));
+            let value = if is_secret {
+                REDACTED.to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    if params.is_empty() {
+        redacted.set_query(None);
+    } else {
+        redacted.query_pairs_mut().clear().extend_pairs(params);
+    }
+    redacted.to_string()
+}
+
+// Endpoint the url is for with the object ids replaced, so `/api/v2/node/XWx8t!children`
+// becomes `/api/v2/node/{id}!children`.  Urls outside the API, such as archive downloads, are
+// reduced to their host.
+pub(crate) fn endpoint_template(url: &url::Url) -> String {
+    let Some(path) = url.path().strip_prefix(API_PATH_PREFIX) else {
+        if url.path().starts_with("/api/") {
+            return url.path().to_string();
+        }
+        return url.host_str().unwrap_or_default().to_string();
+    };
+    let segments: Vec<String> = path
+        .split('/')
+        .enumerate()
+        .map(|(idx, segment)| {
+            // Segments alternate between the object type and its id
+            if idx % 2 == 0 || segment.is_empty() {
+                return segment.to_string();
+            }
+            match segment.split_once('!') {
+                Some((_, action)) => format!("{{id}}!{action}"),
+                None => "{id}".to_string(),
+            }
+        })
+        .collect();
+    format!("{API_PATH_PREFIX}{}", segments.join("/"))
+}
+
+// Span covering a single API call including any retries
+#[cfg(feature = "tracing")]
+pub(crate) struct CallSpan {
+    span: tracing::Span,
+    started: std::time::Instant,
+}
+
+#[cfg(feature = "tracing")]
+impl CallSpan {
+    pub(crate) fn new(method: &Method, url: &url::Url) -> Self {
+        let span = tracing::info_span!(
+            "smugmug.request",
+            method = %method,
+            endpoint = %endpoint_template(url),
+            url = %redact_url(url),
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+            rate_limit_remaining = tracing::field::Empty,
+            error = tracing::field::Empty,
+        );
+        Self {
+            span,
+            started: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn instrument<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+        tracing::Instrument::instrument(fut, self.span.clone())
+    }
+
+    pub(crate) fn record(&self, result: &Result<HttpResponse, SmugMugError>) {
+        self.span
+            .record("latency_ms", self.started.elapsed().as_millis() as u64);
+        match result {
+            Ok(resp) => {
+                self.span.record("status", resp.status().as_u16());
+                let remaining = resp
+                    .headers()
+                    .get("x-ratelimit-remaining")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok());
+                if let Some(remaining) = remaining {
+                    self.span.record("rate_limit_remaining", remaining);
+                }
+            }
+            Err(err) => {
+                self.span.record("error", tracing::field::display(err));
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct CallSpan;
+
+#[cfg(not(feature = "tracing"))]
+impl CallSpan {
+    pub(crate) fn new(_method: &Method, _url: &url::Url) -> Self {
+        Self
+    }
+
+    pub(crate) fn instrument<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+        fut
+    }
+
+    pub(crate) fn record(&self, _result: &Result<HttpResponse, SmugMugError>) {}
+}
+
+// Runs the request for a page of a stream inside a span holding the page number
+pub(crate) fn instrument_page<F: Future>(fut: F, page: u64) -> impl Future<Output = F::Output> {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::Instrument::instrument(fut, tracing::info_span!("smugmug.page", page))
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = page;
+        fut
+    }
+}
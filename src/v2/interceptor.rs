@@ -0,0 +1,48 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::errors::SmugMugError;
+use crate::v2::transport::{HttpRequest, HttpResponse};
+
+/// Hook for injecting cross-cutting behavior into every request made by a
+/// [`crate::v2::Client`] such as custom headers, audit logging, request counting or fault
+/// injection in tests.
+///
+/// Interceptors are registered with [`crate::v2::ClientBuilder::interceptor`] and are called in
+/// the order they were registered.  Each attempt of a retried request is passed through the
+/// interceptors.
+///
+/// ```rust
+/// use smugmug::v2::{Client, HttpRequest, Interceptor, SmugMugError};
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[derive(Default)]
+/// struct RequestCounter(AtomicUsize);
+///
+/// impl Interceptor for RequestCounter {
+///     fn on_request(&self, _req: &mut HttpRequest) -> Result<(), SmugMugError> {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///         Ok(())
+///     }
+/// }
+///
+/// let counter = Arc::new(RequestCounter::default());
+/// let client = Client::builder().interceptor(counter.clone()).build().unwrap();
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called with each outgoing request after it has been signed.  Returning an error fails the
+    /// request without it being sent.
+    fn on_request(&self, _req: &mut HttpRequest) -> Result<(), SmugMugError> {
+        Ok(())
+    }
+
+    /// Called with each response before it is handled.  Returning an error fails the request.
+    fn on_response(&self, _resp: &mut HttpResponse) -> Result<(), SmugMugError> {
+        Ok(())
+    }
+}
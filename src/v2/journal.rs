@@ -0,0 +1,315 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::client::{Method, response_object};
+use crate::v2::errors::SmugMugError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Single mutation recorded in a [`MutationJournal`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Position of the entry in the journal starting from 0
+    pub sequence: u64,
+    /// When the mutation was made
+    pub timestamp: DateTime<Utc>,
+    /// HTTP method of the mutation
+    pub method: String,
+    /// Url the mutation was sent to
+    pub uri: String,
+    /// JSON sent with the mutation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<Value>,
+    /// `Response` member of a GET for the object made before the mutation.  Not fetched for POST.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    /// `Response` member returned by the mutation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+    /// Error the mutation failed with.  The server may have applied it anyway, such as when the
+    /// response timed out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// True if the outcome of the mutation was never recorded, such as when the process stopped
+    /// while it was in flight
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pending: bool,
+}
+
+// Line of the journal file holding either a mutation or the outcome of an earlier one
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JournalLine {
+    Entry(JournalEntry),
+    Outcome(JournalOutcome),
+}
+
+// Outcome of the mutation in the entry with the given sequence number
+#[derive(Serialize, Deserialize)]
+struct JournalOutcome {
+    outcome_of: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    after: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// Request reverting a journaled mutation
+#[derive(Debug)]
+pub(crate) struct InverseRequest {
+    pub(crate) method: Method,
+    pub(crate) uri: String,
+    pub(crate) data: Option<Vec<u8>>,
+}
+
+impl JournalEntry {
+    // Builds the request reverting this mutation.  A PATCH or PUT is reverted by patching the
+    // changed fields back to their values before and a POST by deleting what it created.
+    pub(crate) fn inverse(&self) -> Result<InverseRequest, SmugMugError> {
+        let undo_error =
+            |reason: &str| SmugMugError::JournalUndo(self.sequence, reason.to_string());
+        match self.method.as_str() {
+            "PATCH" | "PUT" => {
+                let Some(Value::Object(changes)) = self.request_body.as_ref() else {
+                    return Err(undo_error("the request body isn't a JSON object"));
+                };
+                let before = self
+                    .before
+                    .as_ref()
+                    .and_then(response_object)
+                    .ok_or_else(|| undo_error("the state before wasn't recorded"))?;
+                let restore: Map<String, Value> = changes
+                    .keys()
+                    .map(|key| (key.clone(), before.get(key).cloned().unwrap_or(Value::Null)))
+                    .collect();
+                Ok(InverseRequest {
+                    method: Method::PATCH,
+                    uri: self.uri.clone(),
+                    data: Some(serde_json::to_vec(&restore)?),
+                })
+            }
+            "POST" => {
+                let uri = self
+                    .after
+                    .as_ref()
+                    .and_then(response_object)
+                    .and_then(|v| v.get("Uri"))
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| undo_error("the created object's uri wasn't recorded"))?;
+                Ok(InverseRequest {
+                    method: Method::DELETE,
+                    uri: uri.to_string(),
+                    data: None,
+                })
+            }
+            _ => Err(undo_error("only PATCH, PUT and POST can be undone")),
+        }
+    }
+}
+
+/// Journal recording each mutation made through a [`crate::v2::Client`] to a JSON lines file.
+///
+/// Every PATCH, POST, PUT and DELETE is written as a [`JournalEntry`] before it is sent, holding
+/// the request along with the object state before (pre-fetched with a GET).  Its outcome, the
+/// object state after or the error it failed with, is added once it completes so failed
+/// mutations the server may have applied are recorded too.  The entries can be passed to
+/// [`crate::v2::Client::undo`] to revert them.  A journal is enabled with
+/// [`crate::v2::ClientBuilder::journal`].  Entries are written on the blocking thread pool and
+/// failures writing them are logged.
+///
+/// ```rust,no_run
+/// use smugmug::v2::{Client, MutationJournal};
+/// use std::sync::Arc;
+///
+/// # async fn run() -> Result<(), smugmug::v2::SmugMugError> {
+/// let journal = Arc::new(MutationJournal::open("upload-key-cleanup.jsonl")?);
+/// let client = Client::builder().journal(journal.clone()).build()?;
+///
+/// // ... run the bulk changes ...
+///
+/// // Revert everything after the first 10 changes
+/// let entries = journal.entries()?;
+/// client.undo(&entries[10..]).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MutationJournal {
+    path: PathBuf,
+    file: Arc<Mutex<JournalFile>>,
+}
+
+#[derive(Debug)]
+struct JournalFile {
+    file: File,
+    next_sequence: u64,
+}
+
+impl MutationJournal {
+    /// Opens the journal at the given path appending to any existing entries
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SmugMugError> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|v| !v.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let next_sequence = if path.exists() {
+            Self::read(&path)?.last().map_or(0, |v| v.sequence + 1)
+        } else {
+            0
+        };
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Arc::new(Mutex::new(JournalFile {
+                file,
+                next_sequence,
+            })),
+        })
+    }
+
+    /// Path of the journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the entries recorded in this journal
+    pub fn entries(&self) -> Result<Vec<JournalEntry>, SmugMugError> {
+        // Hold the lock so a partially written entry isn't read
+        let _file = Self::lock(&self.file);
+        Self::read(&self.path)
+    }
+
+    /// Reads the entries recorded in the journal file at the given path
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<JournalEntry>, SmugMugError> {
+        let mut entries: Vec<JournalEntry> = Vec::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line)? {
+                JournalLine::Entry(entry) => entries.push(entry),
+                JournalLine::Outcome(outcome) => {
+                    if let Some(entry) = entries
+                        .iter_mut()
+                        .rev()
+                        .find(|v| v.sequence == outcome.outcome_of)
+                    {
+                        entry.after = outcome.after;
+                        entry.error = outcome.error;
+                        entry.pending = false;
+                    }
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    // Appends a pending entry for the mutation before it is sent, returning its sequence number
+    // if it was written
+    pub(crate) async fn begin(
+        &self,
+        method: &Method,
+        uri: &str,
+        request_body: Option<&[u8]>,
+        before: Option<Value>,
+    ) -> Option<u64> {
+        let mut entry = JournalEntry {
+            // Assigned when the entry is written
+            sequence: 0,
+            timestamp: Utc::now(),
+            method: method.to_string(),
+            uri: uri.to_string(),
+            request_body: request_body.map(|v| {
+                serde_json::from_slice(v)
+                    .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(v).into_owned()))
+            }),
+            before,
+            after: None,
+            error: None,
+            pending: true,
+        };
+        let file = self.file.clone();
+        let path = self.path.clone();
+        self.run_blocking(move || {
+            let mut file = Self::lock(&file);
+            entry.sequence = file.next_sequence;
+            match Self::append(&mut file, &entry) {
+                Ok(()) => {
+                    file.next_sequence += 1;
+                    Some(entry.sequence)
+                }
+                Err(err) => {
+                    log::warn!(
+                        "Failed writing journal entry for {} {} to {path:?}: {err}",
+                        entry.method,
+                        entry.uri
+                    );
+                    None
+                }
+            }
+        })
+        .await
+        .flatten()
+    }
+
+    // Appends the outcome of the mutation in the entry with the given sequence number
+    pub(crate) async fn complete(
+        &self,
+        sequence: u64,
+        after: Option<Value>,
+        error: Option<String>,
+    ) {
+        let outcome = JournalOutcome {
+            outcome_of: sequence,
+            after,
+            error,
+        };
+        let file = self.file.clone();
+        let path = self.path.clone();
+        self.run_blocking(move || {
+            if let Err(err) = Self::append(&mut Self::lock(&file), &outcome) {
+                log::warn!("Failed writing outcome of journal entry {sequence} to {path:?}: {err}");
+            }
+        })
+        .await;
+    }
+
+    // Runs the file access on the blocking thread pool when within a tokio runtime so the
+    // runtime's worker threads aren't held up
+    async fn run_blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> Option<T> {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => handle
+                .spawn_blocking(f)
+                .await
+                .inspect_err(|err| {
+                    log::warn!("Failed writing journal {:?}: {err}", self.path);
+                })
+                .ok(),
+            Err(_) => Some(f()),
+        }
+    }
+
+    // Writes the value as a line of the journal file
+    fn append(file: &mut JournalFile, value: &impl Serialize) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(value)?;
+        line.push(b'\n');
+        file.file.write_all(&line)
+    }
+
+    fn lock(file: &Mutex<JournalFile>) -> std::sync::MutexGuard<'_, JournalFile> {
+        file.lock().expect("Failed locking the journal")
+    }
+}
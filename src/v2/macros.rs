@@ -36,7 +36,7 @@ macro_rules! obj_update_from_url {
 
 macro_rules! obj_update_from_uri {
     ( $c:expr, $uri: expr, $d: expr,$rt: ty, $r: ident) => {{
-        let req_url = url::Url::parse(API_ORIGIN)?.join($uri)?;
+        let req_url = $c.api_url($uri)?;
         obj_update_from_url!($c, req_url.as_str(), $d, $rt, $r)
     }};
 }
@@ -47,9 +47,7 @@ macro_rules! objs_from_id_slice {
             return Ok(Vec::new());
         }
         let params = vec![("_verbosity", "1")];
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join($uri)?
-            .join($ids.join(",").as_str())?;
+        let req_url = $c.api_url($uri)?.join($ids.join(",").as_str())?;
         // println!("multi-get url: {}", req_url.as_str());
         $c.get::<$rt>(req_url.as_str(), Some(&params))
             .await?
@@ -68,27 +66,69 @@ macro_rules! objs_from_id_slice {
 
 macro_rules! stream_children_from_url {
     ( $c:expr, $url: expr, $params:expr, $rt: ty, $r: ident) => {{
+        stream_children_from_url!(
+            $c,
+            $url,
+            $params,
+            $rt,
+            $r,
+            None,
+            None,
+            crate::v2::cancel::PositionTracker::default()
+        )
+    }};
+    // Resumes from the position if given, ending early once the token is cancelled.  The
+    // tracker is kept up to date with where to resume from.
+    ( $c:expr, $url: expr, $params:expr, $rt: ty, $r: ident, $resume: expr, $cancel: expr,
+      $position: expr) => {{
         let params = vec![("_verbosity", "1")];
+        let resume: Option<crate::v2::PagePosition> = $resume;
+        let cancel: Option<crate::v2::CancellationToken> = $cancel;
+        let position: crate::v2::cancel::PositionTracker = $position;
 
         try_stream! {
             if let Some(url) = $url {
                 // The Pages->NextPage doesn't include verbosity so parsing original params
                 // and dealing with verbosity seperately
-                let mut req_url = url::Url::parse_with_params(API_ORIGIN, $params)?.join(url)?;
-                loop {
-                    let resp = $c.get::<$rt>(
-                        req_url.as_str(), Some(&params)
-                    ).await?
+                let (mut req_url, mut skip) = match resume {
+                    Some(resume) => ($c.api_url(&resume.url)?, resume.offset),
+                    None => (
+                        url::Url::parse_with_params($c.api_origin().as_str(), $params)?
+                            .join(url)?,
+                        0,
+                    ),
+                };
+                let mut page: u64 = 1;
+                'pages: loop {
+                    position.set(&req_url, skip);
+                    let fut = crate::v2::instrument::instrument_page(
+                        $c.get::<$rt>(req_url.as_str(), Some(&params)),
+                        page,
+                    );
+                    let resp = match cancel.as_ref() {
+                        Some(cancel) => match cancel.run_until_cancelled(fut).await {
+                            Some(resp) => resp,
+                            None => break 'pages,
+                        },
+                        None => fut.await,
+                    }?
                     .payload
                     .ok_or(SmugMugError::ResponseMissing())?;
-                    for mut item in resp.$r {
+                    for (idx, mut item) in resp.$r.into_iter().enumerate().skip(skip) {
+                        if cancel.as_ref().is_some_and(|v| v.is_cancelled()) {
+                            break 'pages;
+                        }
+                        position.set(&req_url, idx + 1);
                         item.client = Some($c.clone());
                         yield item
                     }
+                    skip = 0;
 
                     if let Some(next_page) = resp.pages.and_then(|p| p.next_page) {
-                        req_url = url::Url::parse(API_ORIGIN)?.join(&next_page)?;
+                        req_url = $c.api_url(&next_page)?;
+                        page += 1;
                     } else {
+                        position.clear();
                         break;
                     }
                 }
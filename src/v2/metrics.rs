@@ -0,0 +1,287 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::transport::HttpBodyStream;
+use futures::TryStreamExt;
+use http::{Method, StatusCode};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Upper bounds in seconds of the request latency histogram buckets
+pub const LATENCY_BUCKETS: [f64; 10] = [0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Number of requests sent for a method, endpoint and status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestCount {
+    /// HTTP method
+    pub method: String,
+    /// Endpoint with the object ids replaced, such as `/api/v2/node/{id}!children`
+    pub endpoint: String,
+    /// HTTP status, or `error` if no response was received
+    pub status: String,
+    /// Number of requests
+    pub count: u64,
+}
+
+/// Latency histogram of the requests sent for a method and endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyHistogram {
+    /// HTTP method
+    pub method: String,
+    /// Endpoint with the object ids replaced, such as `/api/v2/node/{id}!children`
+    pub endpoint: String,
+    /// Cumulative number of requests completing within each upper bound of [`LATENCY_BUCKETS`]
+    pub buckets: Vec<(f64, u64)>,
+    /// Number of requests
+    pub count: u64,
+    /// Total time taken by the requests in seconds
+    pub sum_seconds: f64,
+}
+
+/// Point in time copy of the metrics collected by a [`crate::v2::Client`].
+///
+/// Requests are counted per attempt, so a request retried after a 429 is counted twice.
+/// Responses served from the caches without a request aren't counted.
+///
+/// ```rust
+/// use smugmug::v2::Client;
+///
+/// let client = Client::builder().build().unwrap();
+/// let metrics = client.metrics();
+/// assert_eq!(metrics.retries, 0);
+///
+/// // Serve from a metrics endpoint for Prometheus to scrape
+/// let text = metrics.to_prometheus();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    /// Requests by method, endpoint and status
+    pub requests: Vec<RequestCount>,
+    /// Request latency by method and endpoint
+    pub latencies: Vec<LatencyHistogram>,
+    /// Response body bytes received
+    pub bytes_downloaded: u64,
+    /// Requests retried per the [`crate::v2::RetryPolicy`]
+    pub retries: u64,
+    /// Responses with a `429 Too Many Requests` status
+    pub too_many_requests: u64,
+    /// Requests remaining in the rate limit window from the last response that reported it
+    pub num_remaining_requests: Option<u64>,
+}
+
+impl MetricsSnapshot {
+    /// Renders the metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        Self::write_header(
+            &mut out,
+            "smugmug_requests_total",
+            "counter",
+            "Requests sent to the SmugMug API",
+        );
+        for v in &self.requests {
+            let _ = writeln!(
+                out,
+                "smugmug_requests_total{{method=\"{}\",endpoint=\"{}\",status=\"{}\"}} {}",
+                escape_label(&v.method),
+                escape_label(&v.endpoint),
+                escape_label(&v.status),
+                v.count
+            );
+        }
+
+        Self::write_header(
+            &mut out,
+            "smugmug_request_duration_seconds",
+            "histogram",
+            "Latency of requests sent to the SmugMug API",
+        );
+        for v in &self.latencies {
+            let labels = format!(
+                "method=\"{}\",endpoint=\"{}\"",
+                escape_label(&v.method),
+                escape_label(&v.endpoint)
+            );
+            for (le, count) in &v.buckets {
+                let _ = writeln!(
+                    out,
+                    "smugmug_request_duration_seconds_bucket{{{labels},le=\"{le}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "smugmug_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                v.count
+            );
+            let _ = writeln!(
+                out,
+                "smugmug_request_duration_seconds_sum{{{labels}}} {}",
+                v.sum_seconds
+            );
+            let _ = writeln!(
+                out,
+                "smugmug_request_duration_seconds_count{{{labels}}} {}",
+                v.count
+            );
+        }
+
+        let counters = [
+            (
+                "smugmug_downloaded_bytes_total",
+                "Response body bytes received from SmugMug",
+                self.bytes_downloaded,
+            ),
+            (
+                "smugmug_retries_total",
+                "Requests retried after a failure",
+                self.retries,
+            ),
+            (
+                "smugmug_too_many_requests_total",
+                "Responses with a 429 Too Many Requests status",
+                self.too_many_requests,
+            ),
+        ];
+        for (name, help, value) in counters {
+            Self::write_header(&mut out, name, "counter", help);
+            let _ = writeln!(out, "{name} {value}");
+        }
+
+        if let Some(remaining) = self.num_remaining_requests {
+            Self::write_header(
+                &mut out,
+                "smugmug_rate_limit_remaining",
+                "gauge",
+                "Requests remaining in the current rate limit window",
+            );
+            let _ = writeln!(out, "smugmug_rate_limit_remaining {remaining}");
+        }
+        out
+    }
+
+    fn write_header(out: &mut String, name: &str, metric_type: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {metric_type}");
+    }
+}
+
+// Escapes a label value per the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Metrics collected by the client
+#[derive(Debug, Default)]
+pub(crate) struct RequestMetrics {
+    state: Mutex<MetricsState>,
+    bytes_downloaded: Arc<AtomicU64>,
+    retries: AtomicU64,
+    too_many_requests: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    requests: BTreeMap<(String, String, String), u64>,
+    latencies: BTreeMap<(String, String), Latencies>,
+}
+
+#[derive(Debug, Default)]
+struct Latencies {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum_seconds: f64,
+}
+
+impl RequestMetrics {
+    // Records a single attempt at sending a request
+    pub(crate) fn record_request(
+        &self,
+        method: &Method,
+        endpoint: String,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        if status == Some(StatusCode::TOO_MANY_REQUESTS) {
+            self.too_many_requests.fetch_add(1, Ordering::Relaxed);
+        }
+        let status = status.map_or_else(|| "error".to_string(), |v| v.as_u16().to_string());
+        let seconds = latency.as_secs_f64();
+
+        let mut state = self.state.lock().expect("Failed locking the metrics");
+        *state
+            .requests
+            .entry((method.to_string(), endpoint.clone(), status))
+            .or_default() += 1;
+        let latencies = state
+            .latencies
+            .entry((method.to_string(), endpoint))
+            .or_default();
+        if let Some(idx) = LATENCY_BUCKETS.iter().position(|v| seconds <= *v) {
+            latencies.buckets[idx] += 1;
+        }
+        latencies.count += 1;
+        latencies.sum_seconds += seconds;
+    }
+
+    pub(crate) fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Wraps the body so the bytes are counted as they are received
+    pub(crate) fn count_body(&self, body: HttpBodyStream) -> HttpBodyStream {
+        let bytes_downloaded = self.bytes_downloaded.clone();
+        Box::pin(body.inspect_ok(move |chunk| {
+            bytes_downloaded.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        }))
+    }
+
+    pub(crate) fn snapshot(&self, num_remaining_requests: Option<u64>) -> MetricsSnapshot {
+        let state = self.state.lock().expect("Failed locking the metrics");
+        let requests = state
+            .requests
+            .iter()
+            .map(|((method, endpoint, status), count)| RequestCount {
+                method: method.clone(),
+                endpoint: endpoint.clone(),
+                status: status.clone(),
+                count: *count,
+            })
+            .collect();
+        let latencies = state
+            .latencies
+            .iter()
+            .map(|((method, endpoint), latencies)| LatencyHistogram {
+                method: method.clone(),
+                endpoint: endpoint.clone(),
+                buckets: LATENCY_BUCKETS
+                    .iter()
+                    .zip(latencies.buckets.iter().scan(0, |total, v| {
+                        *total += v;
+                        Some(*total)
+                    }))
+                    .map(|(le, count)| (*le, count))
+                    .collect(),
+                count: latencies.count,
+                sum_seconds: latencies.sum_seconds,
+            })
+            .collect();
+        MetricsSnapshot {
+            requests,
+            latencies,
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            too_many_requests: self.too_many_requests.load(Ordering::Relaxed),
+            num_remaining_requests,
+        }
+    }
+}
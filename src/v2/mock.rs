@@ -0,0 +1,907 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+//! In-process mock of the SmugMug v2 API for testing without credentials.
+//!
+//! *Requires the `mock` feature*
+//!
+//! The [`MockServer`] serves the endpoints used by this crate from an in-memory [`MockLibrary`]
+//! seeded with synthetic folders, albums and images.  This covers `!authuser`, users, nodes and
+//! their children, albums and their images, images and their archives, multi-get id lists,
+//! PATCH updates, album creation, node deletion, `Pages.NextPage` pagination, rate limit headers
+//! and 429 responses.
+//!
+//! ```rust
+//! use futures::{pin_mut, StreamExt};
+//! use smugmug::v2::mock::{MockLibrary, MockServer};
+//! use smugmug::v2::{NodeTypeFilters, SortDirection, SortMethod, User};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), smugmug::v2::SmugMugError> {
+//! let mut library = MockLibrary::new("apidemo");
+//! let root = library.root_node_id().to_string();
+//! let album_key = library.add_album(&root, "Vacation");
+//! library.add_image(&album_key, "beach.jpg", b"not really a jpeg".to_vec());
+//!
+//! let server = MockServer::start(library).await?;
+//! let client = server.client_builder().build()?;
+//!
+//! let node = User::authenticated_user_info(client).await?.node().await?;
+//! let children = node.children(
+//!     NodeTypeFilters::Any,
+//!     SortDirection::Ascending,
+//!     SortMethod::SortIndex,
+//! )?;
+//! pin_mut!(children);
+//! let album = children.next().await.unwrap()?.album().await?;
+//! assert_eq!(album.name, "Vacation");
+//! # Ok(())
+//! # }
+//! ```
+use crate::v2::{Client, ClientBuilder, Creds, SmugMugError};
+use bytes::Bytes;
+use http::header::{CONTENT_RANGE, CONTENT_TYPE, RANGE, RETRY_AFTER};
+use http::{HeaderValue, Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const API_PREFIX: &str = "/api/v2/";
+const ARCHIVE_PREFIX: &str = "/mock/archive/";
+const TIMESTAMP: &str = "2025-01-01T00:00:00Z";
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// In-memory model of a SmugMug library served by the [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct MockLibrary {
+    user_name: String,
+    root_node_id: String,
+    nodes: BTreeMap<String, MockNode>,
+    albums: BTreeMap<String, MockAlbum>,
+    images: BTreeMap<String, MockImage>,
+    page_size: usize,
+    rate_limit: Option<u32>,
+    next_id: u64,
+}
+
+#[derive(Debug, Clone)]
+struct MockNode {
+    name: String,
+    node_type: &'static str,
+    parent_id: Option<String>,
+    children: Vec<String>,
+    album_key: Option<String>,
+    fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone)]
+struct MockAlbum {
+    name: String,
+    images: Vec<String>,
+    fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone)]
+struct MockImage {
+    file_name: String,
+    data: Bytes,
+    fields: Map<String, Value>,
+}
+
+impl MockLibrary {
+    /// Creates a library for the given user holding only the root folder
+    pub fn new(user_name: &str) -> Self {
+        let mut library = Self {
+            user_name: user_name.to_string(),
+            root_node_id: String::new(),
+            nodes: BTreeMap::new(),
+            albums: BTreeMap::new(),
+            images: BTreeMap::new(),
+            page_size: 50,
+            rate_limit: None,
+            next_id: 0,
+        };
+        let root_id = library.next_id("N");
+        library.nodes.insert(
+            root_id.clone(),
+            MockNode {
+                name: user_name.to_string(),
+                node_type: "Folder",
+                parent_id: None,
+                children: Vec::new(),
+                album_key: None,
+                fields: Map::new(),
+            },
+        );
+        library.root_node_id = root_id;
+        library
+    }
+
+    /// Number of items returned per page when listing node children and album images.
+    /// Defaults to 50.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Number of requests allowed per 60 second rate limit window.  Once used up the server
+    /// responds with a 429 until the window resets.  Defaults to unlimited, in which case no
+    /// rate limit headers are sent.
+    pub fn with_rate_limit(mut self, requests_per_window: u32) -> Self {
+        self.rate_limit = Some(requests_per_window);
+        self
+    }
+
+    /// Id of the user's root folder node
+    pub fn root_node_id(&self) -> &str {
+        &self.root_node_id
+    }
+
+    /// Adds a folder under the given node returning the new node id
+    pub fn add_folder(&mut self, parent_id: &str, name: &str) -> String {
+        self.add_node(parent_id, name, "Folder", None)
+    }
+
+    /// Adds an album under the given node returning the new album key
+    pub fn add_album(&mut self, parent_id: &str, name: &str) -> String {
+        let album_key = self.next_id("A");
+        self.albums.insert(
+            album_key.clone(),
+            MockAlbum {
+                name: name.to_string(),
+                images: Vec::new(),
+                fields: Map::new(),
+            },
+        );
+        self.add_node(parent_id, name, "Album", Some(album_key.clone()));
+        album_key
+    }
+
+    /// Adds an image to the album returning the new image key.  The data is served as the
+    /// image archive.
+    pub fn add_image(&mut self, album_key: &str, file_name: &str, data: Vec<u8>) -> String {
+        let image_key = self.next_id("I");
+        self.images.insert(
+            image_key.clone(),
+            MockImage {
+                file_name: file_name.to_string(),
+                data: data.into(),
+                fields: Map::new(),
+            },
+        );
+        self.albums
+            .get_mut(album_key)
+            .expect("Album must exist before adding images")
+            .images
+            .push(image_key.clone());
+        image_key
+    }
+
+    /// Id of the node holding the album
+    pub fn album_node_id(&self, album_key: &str) -> Option<&str> {
+        self.nodes
+            .iter()
+            .find(|(_, node)| node.album_key.as_deref() == Some(album_key))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Ids of the child nodes of the given node
+    pub fn child_node_ids(&self, node_id: &str) -> Vec<String> {
+        self.nodes
+            .get(node_id)
+            .map(|v| v.children.clone())
+            .unwrap_or_default()
+    }
+
+    /// Fields changed on the node with PATCH requests
+    pub fn node_fields(&self, node_id: &str) -> Option<&Map<String, Value>> {
+        self.nodes.get(node_id).map(|v| &v.fields)
+    }
+
+    /// Fields changed on the album with PATCH requests
+    pub fn album_fields(&self, album_key: &str) -> Option<&Map<String, Value>> {
+        self.albums.get(album_key).map(|v| &v.fields)
+    }
+
+    /// Fields changed on the image with PATCH requests
+    pub fn image_fields(&self, image_key: &str) -> Option<&Map<String, Value>> {
+        self.images.get(image_key).map(|v| &v.fields)
+    }
+
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}{:06}", self.next_id)
+    }
+
+    fn add_node(
+        &mut self,
+        parent_id: &str,
+        name: &str,
+        node_type: &'static str,
+        album_key: Option<String>,
+    ) -> String {
+        let node_id = self.next_id("N");
+        self.nodes
+            .get_mut(parent_id)
+            .expect("Parent node must exist before adding children")
+            .children
+            .push(node_id.clone());
+        self.nodes.insert(
+            node_id.clone(),
+            MockNode {
+                name: name.to_string(),
+                node_type,
+                parent_id: Some(parent_id.to_string()),
+                children: Vec::new(),
+                album_key,
+                fields: Map::new(),
+            },
+        );
+        node_id
+    }
+
+    fn user_json(&self) -> Value {
+        json!({
+            "Uri": format!("/api/v2/user/{}", self.user_name),
+            "Name": self.user_name,
+            "NickName": self.user_name,
+            "WebUri": format!("https://{}.smugmug.com", self.user_name),
+            "Uris": { "Node": format!("/api/v2/node/{}", self.root_node_id) }
+        })
+    }
+
+    fn node_json(&self, node_id: &str) -> Option<Value> {
+        let node = self.nodes.get(node_id)?;
+        let url_name = url_name(&node.name);
+        let mut uris = json!({ "ChildNodes": format!("/api/v2/node/{node_id}!children") });
+        if let Some(parent_id) = node.parent_id.as_ref() {
+            uris["ParentNode"] = json!(format!("/api/v2/node/{parent_id}"));
+        }
+        if let Some(album_key) = node.album_key.as_ref() {
+            uris["Album"] = json!(format!("/api/v2/album/{album_key}"));
+        }
+        let value = json!({
+            "Uri": format!("/api/v2/node/{node_id}"),
+            "Name": node.name,
+            "UrlName": url_name,
+            "WebUri": format!("https://{}.smugmug.com/{url_name}", self.user_name),
+            "Privacy": "Public",
+            "HasChildren": !node.children.is_empty(),
+            "IsRoot": node.parent_id.is_none(),
+            "NodeID": node_id,
+            "Type": node.node_type,
+            "DateAdded": TIMESTAMP,
+            "DateModified": TIMESTAMP,
+            "Uris": uris,
+        });
+        Some(with_fields(value, &node.fields))
+    }
+
+    fn album_json(&self, album_key: &str) -> Option<Value> {
+        let album = self.albums.get(album_key)?;
+        let url_name = url_name(&album.name);
+        let total_size: usize = album
+            .images
+            .iter()
+            .filter_map(|v| self.images.get(v))
+            .map(|v| v.data.len())
+            .sum();
+        let value = json!({
+            "Uri": format!("/api/v2/album/{album_key}"),
+            "AlbumKey": album_key,
+            "AllowDownloads": true,
+            "Name": album.name,
+            "UrlName": url_name,
+            "WebUri": format!("https://{}.smugmug.com/{url_name}", self.user_name),
+            "ImageCount": album.images.len(),
+            "TotalSizes": total_size,
+            "OriginalSizes": total_size,
+            "Privacy": "Public",
+            "Date": TIMESTAMP,
+            "ImagesLastUpdated": TIMESTAMP,
+            "LastUpdated": TIMESTAMP,
+            "Uris": { "AlbumImages": format!("/api/v2/album/{album_key}!images") },
+        });
+        Some(with_fields(value, &album.fields))
+    }
+
+    fn image_json(&self, origin: &str, image_key: &str) -> Option<Value> {
+        let image = self.images.get(image_key)?;
+        let value = json!({
+            "Uri": format!("/api/v2/image/{image_key}-0"),
+            "Title": image.file_name,
+            "Caption": "",
+            "Altitude": 0,
+            "Format": image
+                .file_name
+                .rsplit_once('.')
+                .map(|(_, ext)| ext.to_uppercase())
+                .unwrap_or_default(),
+            "FileName": image.file_name,
+            "ImageKey": image_key,
+            "KeywordArray": [],
+            "ArchivedUri": format!("{origin}{ARCHIVE_PREFIX}{image_key}"),
+            "ArchivedMD5": format!("{:x}", md5::compute(&image.data)),
+            "ArchivedSize": image.data.len(),
+            "Processing": false,
+            "IsVideo": false,
+            "Hidden": false,
+            "Watermarked": false,
+            "DateTimeUploaded": TIMESTAMP,
+            "LastUpdated": TIMESTAMP,
+        });
+        Some(with_fields(value, &image.fields))
+    }
+}
+
+/// Mock SmugMug API server running in the background on a local port.  The server is stopped
+/// when this is dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    origin: String,
+    state: Arc<MockState>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    origin: String,
+    library: Mutex<MockLibrary>,
+    rate_window: Mutex<RateWindow>,
+    forced_failures: Mutex<VecDeque<u64>>,
+    request_count: Mutex<usize>,
+}
+
+#[derive(Debug)]
+struct RateWindow {
+    used: u32,
+    resets_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl MockServer {
+    /// Starts serving the library on a random local port
+    pub async fn start(library: MockLibrary) -> Result<Self, SmugMugError> {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let origin = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(MockState {
+            origin: origin.clone(),
+            library: Mutex::new(library),
+            rate_window: Mutex::new(RateWindow::new()),
+            forced_failures: Mutex::new(VecDeque::new()),
+            request_count: Mutex::new(0),
+        });
+
+        let server_state = state.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        // Errors such as running out of file descriptors clear up once
+                        // connections close, so wait a little instead of spinning
+                        log::warn!("Mock server failed accepting a connection: {err}");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| {
+                        let state = state.clone();
+                        async move { Ok::<_, Infallible>(state.handle(req).await) }
+                    });
+                    if let Err(err) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        log::debug!("Mock server connection error: {err}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            origin,
+            state,
+            task,
+        })
+    }
+
+    /// Origin the server is listening on such as `http://127.0.0.1:4321`
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// Client builder pointed at this server with placeholder credentials, so PATCH and POST
+    /// requests are signed as they would be against the real API
+    pub fn client_builder(&self) -> ClientBuilder {
+        Client::builder()
+            .api_origin(&self.origin)
+            .upload_origin(&self.origin)
+            .creds(Creds::from_tokens(
+                "mock-api-key",
+                Some("mock-api-secret"),
+                Some("mock-token"),
+                Some("mock-token-secret"),
+            ))
+    }
+
+    /// Access to the library for seeding more data or checking the result of updates
+    pub fn library(&self) -> MutexGuard<'_, MockLibrary> {
+        self.state.library()
+    }
+
+    /// Responds to the next `count` requests with a 429 asking to retry after the given seconds
+    pub fn fail_next_with_too_many_requests(&self, count: usize, retry_after: u64) {
+        let mut failures = self
+            .state
+            .forced_failures
+            .lock()
+            .expect("Failed locking mock forced failures");
+        failures.extend(std::iter::repeat_n(retry_after, count));
+    }
+
+    /// Number of requests received so far
+    pub fn request_count(&self) -> usize {
+        *self
+            .state
+            .request_count
+            .lock()
+            .expect("Failed locking mock request count")
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl RateWindow {
+    fn new() -> Self {
+        Self {
+            used: 0,
+            resets_at: chrono::Utc::now() + RATE_LIMIT_WINDOW,
+        }
+    }
+}
+
+// Request routed to one of the API endpoints
+struct Route<'a> {
+    kind: &'a str,
+    ids: Vec<&'a str>,
+    action: Option<&'a str>,
+}
+
+impl MockState {
+    fn library(&self) -> MutexGuard<'_, MockLibrary> {
+        self.library.lock().expect("Failed locking mock library")
+    }
+
+    async fn handle(&self, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        *self
+            .request_count
+            .lock()
+            .expect("Failed locking mock request count") += 1;
+
+        let forced_failure = self
+            .forced_failures
+            .lock()
+            .expect("Failed locking mock forced failures")
+            .pop_front();
+        if let Some(retry_after) = forced_failure {
+            return too_many_requests(retry_after);
+        }
+
+        // Rate limit accounting
+        let rate_limit = self.library().rate_limit;
+        let mut rate_headers = Vec::new();
+        if let Some(limit) = rate_limit {
+            let mut window = self
+                .rate_window
+                .lock()
+                .expect("Failed locking mock rate window");
+            let now = chrono::Utc::now();
+            if window.resets_at <= now {
+                *window = RateWindow::new();
+            }
+            if window.used >= limit {
+                return too_many_requests((window.resets_at - now).num_seconds().max(1) as u64);
+            }
+            window.used += 1;
+            rate_headers.push(("X-RateLimit-Limit", limit.to_string()));
+            rate_headers.push(("X-RateLimit-Remaining", (limit - window.used).to_string()));
+            rate_headers.push((
+                "X-RateLimit-Reset",
+                window.resets_at.timestamp().to_string(),
+            ));
+        }
+
+        let (parts, body) = req.into_parts();
+        let body = match body.collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(_) => return error_response(StatusCode::BAD_REQUEST, "Bad Request"),
+        };
+        let query: BTreeMap<String, String> = parts
+            .uri
+            .query()
+            .map(|v| {
+                url::form_urlencoded::parse(v.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let path = parts.uri.path();
+        let mut resp = if let Some(image_key) = path.strip_prefix(ARCHIVE_PREFIX) {
+            self.archive(image_key, parts.headers.get(RANGE))
+        } else {
+            self.api(&parts.method, path, &query, &body)
+        };
+        for (name, value) in rate_headers {
+            resp.headers_mut()
+                .insert(name, HeaderValue::from_str(&value).expect("valid header"));
+        }
+        resp
+    }
+
+    fn api(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &BTreeMap<String, String>,
+        body: &Bytes,
+    ) -> Response<Full<Bytes>> {
+        if path == "/api/v2!authuser" && method == Method::GET {
+            return ok_response(json!({ "User": self.library().user_json() }));
+        }
+        let Some(route) = Self::route(path) else {
+            return error_response(StatusCode::NOT_FOUND, "Not Found");
+        };
+
+        match (method, route.kind, route.action) {
+            (&Method::GET, "user", None) => {
+                let library = self.library();
+                if route.ids != [library.user_name.as_str()] {
+                    return error_response(StatusCode::NOT_FOUND, "Not Found");
+                }
+                ok_response(json!({ "User": library.user_json() }))
+            }
+            (&Method::GET, "node", None) => {
+                self.get_objects(&route.ids, "Node", |library, id| library.node_json(id))
+            }
+            (&Method::GET, "album", None) => {
+                self.get_objects(&route.ids, "Album", |library, id| library.album_json(id))
+            }
+            (&Method::GET, "image", None) => {
+                self.get_objects(&route.ids, "Image", |library, id| {
+                    library.image_json(&self.origin, id)
+                })
+            }
+            (&Method::GET, "node", Some("children")) => {
+                let library = self.library();
+                let Some(node) = library.nodes.get(route.ids[0]) else {
+                    return error_response(StatusCode::NOT_FOUND, "Not Found");
+                };
+                let type_filter = query.get("Type").filter(|v| v.as_str() != "Any");
+                let children: Vec<Value> = node
+                    .children
+                    .iter()
+                    .filter(|id| {
+                        type_filter.is_none_or(|filter| {
+                            library
+                                .nodes
+                                .get(*id)
+                                .is_some_and(|v| v.node_type == filter.as_str())
+                        })
+                    })
+                    .filter_map(|id| library.node_json(id))
+                    .collect();
+                page_response(path, query, "Node", children, library.page_size)
+            }
+            (&Method::GET, "album", Some("images")) => {
+                let library = self.library();
+                let Some(album) = library.albums.get(route.ids[0]) else {
+                    return error_response(StatusCode::NOT_FOUND, "Not Found");
+                };
+                let images: Vec<Value> = album
+                    .images
+                    .iter()
+                    .filter_map(|id| library.image_json(&self.origin, id))
+                    .collect();
+                page_response(path, query, "AlbumImage", images, library.page_size)
+            }
+            (&Method::POST, "node", Some("children")) => self.create_node(route.ids[0], body),
+            (&Method::PATCH, kind, None) if route.ids.len() == 1 => {
+                self.patch_object(kind, route.ids[0], body)
+            }
+            (&Method::DELETE, "node", None) if route.ids.len() == 1 => {
+                self.delete_node(route.ids[0])
+            }
+            _ => error_response(StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed"),
+        }
+    }
+
+    // Splits `/api/v2/node/abc,def!children` into its parts
+    fn route(path: &str) -> Option<Route<'_>> {
+        let (kind, rest) = path.strip_prefix(API_PREFIX)?.split_once('/')?;
+        let (ids, action) = match rest.split_once('!') {
+            Some((ids, action)) => (ids, Some(action)),
+            None => (rest, None),
+        };
+        let ids: Vec<&str> = ids
+            .split(',')
+            // Image uris carry a size suffix such as `-0`
+            .map(|v| match kind {
+                "image" => v.split_once('-').map_or(v, |(key, _)| key),
+                _ => v,
+            })
+            .filter(|v| !v.is_empty())
+            .collect();
+        if ids.is_empty() {
+            return None;
+        }
+        Some(Route { kind, ids, action })
+    }
+
+    // Returns a single object or a list for a multi-get
+    fn get_objects(
+        &self,
+        ids: &[&str],
+        name: &str,
+        to_json: impl Fn(&MockLibrary, &str) -> Option<Value>,
+    ) -> Response<Full<Bytes>> {
+        let library = self.library();
+        let objects: Option<Vec<Value>> = ids.iter().map(|id| to_json(&library, id)).collect();
+        match objects {
+            Some(mut objects) if objects.len() == 1 => {
+                ok_response(json!({ name: objects.remove(0) }))
+            }
+            Some(objects) => ok_response(json!({ name: objects })),
+            None => error_response(StatusCode::NOT_FOUND, "Not Found"),
+        }
+    }
+
+    fn create_node(&self, parent_id: &str, body: &Bytes) -> Response<Full<Bytes>> {
+        let Ok(Value::Object(mut props)) = serde_json::from_slice::<Value>(body) else {
+            return error_response(StatusCode::BAD_REQUEST, "Bad Request");
+        };
+        let name = match props.remove("Name") {
+            Some(Value::String(name)) => name,
+            _ => return error_response(StatusCode::BAD_REQUEST, "Name is required"),
+        };
+        let node_type = props.remove("Type");
+
+        let mut library = self.library();
+        if !library.nodes.contains_key(parent_id) {
+            return error_response(StatusCode::NOT_FOUND, "Not Found");
+        }
+        let node_id = match node_type.as_ref().and_then(|v| v.as_str()) {
+            Some("Album") => {
+                let album_key = library.add_album(parent_id, &name);
+                if let Some(album) = library.albums.get_mut(&album_key) {
+                    album.fields = props;
+                }
+                library
+                    .album_node_id(&album_key)
+                    .expect("album node was just added")
+                    .to_string()
+            }
+            Some("Folder") => library.add_folder(parent_id, &name),
+            _ => return error_response(StatusCode::BAD_REQUEST, "Unsupported node type"),
+        };
+        let node = library.node_json(&node_id);
+        json_response(StatusCode::CREATED, json!({ "Node": node }))
+    }
+
+    fn patch_object(&self, kind: &str, id: &str, body: &Bytes) -> Response<Full<Bytes>> {
+        let Ok(Value::Object(changes)) = serde_json::from_slice::<Value>(body) else {
+            return error_response(StatusCode::BAD_REQUEST, "Bad Request");
+        };
+
+        let mut library = self.library();
+        let (name, fields) = match kind {
+            "node" => (
+                "Node",
+                library.nodes.get_mut(id).map(|v| {
+                    apply_name(&mut v.name, &changes, "Name");
+                    &mut v.fields
+                }),
+            ),
+            "album" => (
+                "Album",
+                library.albums.get_mut(id).map(|v| {
+                    apply_name(&mut v.name, &changes, "Name");
+                    &mut v.fields
+                }),
+            ),
+            "image" => ("Image", library.images.get_mut(id).map(|v| &mut v.fields)),
+            _ => ("", None),
+        };
+        let Some(fields) = fields else {
+            return error_response(StatusCode::NOT_FOUND, "Not Found");
+        };
+        fields.extend(changes);
+
+        let object = match kind {
+            "node" => library.node_json(id),
+            "album" => library.album_json(id),
+            _ => library.image_json(&self.origin, id),
+        };
+        ok_response(json!({ name: object }))
+    }
+
+    // Removes the node along with everything under it
+    fn delete_node(&self, node_id: &str) -> Response<Full<Bytes>> {
+        let mut library = self.library();
+        let Some(parent_id) = library.nodes.get(node_id).map(|v| v.parent_id.clone()) else {
+            return error_response(StatusCode::NOT_FOUND, "Not Found");
+        };
+        let Some(parent_id) = parent_id else {
+            return error_response(StatusCode::FORBIDDEN, "The root node can't be deleted");
+        };
+        if let Some(parent) = library.nodes.get_mut(&parent_id) {
+            parent.children.retain(|v| v != node_id);
+        }
+
+        let mut removing = vec![node_id.to_string()];
+        while let Some(id) = removing.pop() {
+            let Some(node) = library.nodes.remove(&id) else {
+                continue;
+            };
+            if let Some(album) = node.album_key.and_then(|v| library.albums.remove(&v)) {
+                for image_key in album.images {
+                    library.images.remove(&image_key);
+                }
+            }
+            removing.extend(node.children);
+        }
+        ok_response(Value::Null)
+    }
+
+    fn archive(&self, image_key: &str, range: Option<&HeaderValue>) -> Response<Full<Bytes>> {
+        let library = self.library();
+        let Some(image) = library.images.get(image_key) else {
+            return error_response(StatusCode::NOT_FOUND, "Not Found");
+        };
+        let len = image.data.len();
+        let start = range
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes="))
+            .and_then(|v| v.strip_suffix('-'))
+            .and_then(|v| v.parse::<usize>().ok());
+        match start {
+            Some(start) if start >= len => {
+                let mut resp =
+                    error_response(StatusCode::RANGE_NOT_SATISFIABLE, "Range Not Satisfiable");
+                resp.headers_mut().insert(
+                    CONTENT_RANGE,
+                    HeaderValue::from_str(&format!("bytes */{len}")).expect("valid header"),
+                );
+                resp
+            }
+            Some(start) => {
+                let mut resp = Response::new(Full::new(image.data.slice(start..)));
+                *resp.status_mut() = StatusCode::PARTIAL_CONTENT;
+                resp.headers_mut().insert(
+                    CONTENT_RANGE,
+                    HeaderValue::from_str(&format!("bytes {start}-{}/{len}", len - 1))
+                        .expect("valid header"),
+                );
+                resp
+            }
+            None => Response::new(Full::new(image.data.clone())),
+        }
+    }
+}
+
+// Renames the object if the changes include a new name
+fn apply_name(name: &mut String, changes: &Map<String, Value>, field: &str) {
+    if let Some(Value::String(new_name)) = changes.get(field) {
+        *name = new_name.clone();
+    }
+}
+
+// Overlays the fields changed with PATCH requests
+fn with_fields(mut value: Value, fields: &Map<String, Value>) -> Value {
+    if let Some(object) = value.as_object_mut() {
+        object.extend(fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    value
+}
+
+fn url_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+// Returns one page of the items along with the `Pages` information
+fn page_response(
+    path: &str,
+    query: &BTreeMap<String, String>,
+    name: &str,
+    items: Vec<Value>,
+    page_size: usize,
+) -> Response<Full<Bytes>> {
+    let total = items.len();
+    let start = query
+        .get("start")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+    let count = query
+        .get("count")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(page_size)
+        .clamp(1, page_size);
+    let page: Vec<Value> = items.into_iter().skip(start - 1).take(count).collect();
+
+    let mut pages = json!({
+        "Total": total,
+        "Start": start,
+        "Count": page.len(),
+        "RequestedCount": count,
+    });
+    let next_start = start + count;
+    if next_start <= total {
+        // Like the real API the next page keeps the original filters
+        let mut next_query = url::form_urlencoded::Serializer::new(String::new());
+        for (key, value) in query
+            .iter()
+            .filter(|(k, _)| !k.starts_with('_') && *k != "start" && *k != "count")
+        {
+            next_query.append_pair(key, value);
+        }
+        next_query.append_pair("start", &next_start.to_string());
+        next_query.append_pair("count", &count.to_string());
+        pages["NextPage"] = json!(format!("{path}?{}", next_query.finish()));
+    }
+    ok_response(json!({ name: page, "Pages": pages }))
+}
+
+fn ok_response(response: Value) -> Response<Full<Bytes>> {
+    json_response(StatusCode::OK, response)
+}
+
+fn json_response(status: StatusCode, response: Value) -> Response<Full<Bytes>> {
+    let body = json!({
+        "Code": status.as_u16(),
+        "Message": status.canonical_reason().unwrap_or_default(),
+        "Response": response,
+    });
+    let mut resp = Response::new(Full::new(Bytes::from(body.to_string())));
+    *resp.status_mut() = status;
+    resp.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    resp
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    let body = json!({ "Code": status.as_u16(), "Message": message });
+    let mut resp = Response::new(Full::new(Bytes::from(body.to_string())));
+    *resp.status_mut() = status;
+    resp.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    resp
+}
+
+fn too_many_requests(retry_after: u64) -> Response<Full<Bytes>> {
+    let mut resp = error_response(StatusCode::TOO_MANY_REQUESTS, "Too Many Requests");
+    resp.headers_mut().insert(
+        RETRY_AFTER,
+        HeaderValue::from_str(&retry_after.to_string()).expect("valid header"),
+    );
+    resp
+}
@@ -6,20 +6,55 @@
  *  at your option.
  */
 
+pub mod accounts;
 pub mod album;
+pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod budget;
+pub mod cancel;
+pub mod cassette;
 pub mod client;
+pub mod download;
 pub mod errors;
+pub mod http_cache;
 pub mod image;
+mod instrument;
+pub mod interceptor;
+pub mod journal;
 mod macros;
+pub mod metrics;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod node;
+pub mod object_cache;
 mod parsers;
 pub mod properties;
+pub mod rate_limit_store;
+pub mod retry;
+pub mod throttle;
+pub mod transport;
 pub mod user;
 
+pub use accounts::*;
 pub use album::*;
+pub use api::*;
+pub use budget::*;
+pub use cancel::*;
+pub use cassette::*;
 pub use client::*;
+pub use download::*;
 pub use errors::*;
+pub use http_cache::*;
 pub use image::*;
+pub use interceptor::*;
+pub use journal::*;
+pub use metrics::*;
 pub use node::*;
+pub use object_cache::*;
 pub use properties::*;
+pub use rate_limit_store::*;
+pub use retry::*;
+pub use throttle::*;
+pub use transport::*;
 pub use user::*;
@@ -5,6 +5,7 @@
  *  - MIT license <http://opensource.org/licenses/MIT>
  *  at your option.
  */
+use crate::v2::cancel::PositionTracker;
 use crate::v2::errors::SmugMugError;
 use crate::v2::macros::{
     obj_from_url, obj_update_from_uri, obj_update_from_url, objs_from_id_slice,
@@ -12,8 +13,8 @@ use crate::v2::macros::{
 };
 use crate::v2::parsers::{from_node_type, from_privacy, is_none_or_empty_str};
 use crate::v2::{
-    Album, Client, CreateAlbumProps, NodeType, NodeTypeFilters, Pages, PrivacyLevel, SortDirection,
-    SortMethod, API_ORIGIN,
+    API_ORIGIN, Album, CancellableStream, CancellationToken, Client, CreateAlbumProps, NodeType,
+    NodeTypeFilters, PagePosition, Pages, PrivacyLevel, SortDirection, SortMethod,
 };
 use async_stream::try_stream;
 use chrono::{DateTime, Utc};
@@ -96,9 +97,7 @@ impl Node {
 
     /// Returns information for the specified node id using the provided client
     pub async fn from_id(client: Client, id: &str) -> Result<Self, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join(Self::BASE_URI)?
-            .join(id)?;
+        let req_url = client.api_url(Self::BASE_URI)?.join(id)?;
         Self::from_url(client, req_url.as_str()).await
     }
 
@@ -125,25 +124,21 @@ impl Node {
         data: Vec<u8>,
         id: &str,
     ) -> Result<Node, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join(Self::BASE_URI)?
-            .join(id)?;
+        let req_url = client.api_url(Self::BASE_URI)?.join(id)?;
         obj_update_from_url!(client, req_url.as_str(), data, NodeResponse, node)
     }
 
     /// Retrieves the Album specific information about this Node
     pub async fn album(&self) -> Result<Album, SmugMugError> {
         let album_uri = self.uris.album.as_ref().ok_or(SmugMugError::NotAnAlbum())?;
-        let req_url = url::Url::parse(API_ORIGIN)?.join(album_uri)?;
+        let client = self
+            .client
+            .as_ref()
+            .ok_or(SmugMugError::ClientNotFound())?
+            .clone();
+        let req_url = client.api_url(album_uri)?;
 
-        Album::from_url(
-            self.client
-                .as_ref()
-                .ok_or(SmugMugError::ClientNotFound())?
-                .clone(),
-            req_url.as_str(),
-        )
-            .await
+        Album::from_url(client, req_url.as_str()).await
     }
 
     /// Retrieves the album id if this node is an [`Album`] type
@@ -165,21 +160,25 @@ impl Node {
         album_props: CreateAlbumProps,
     ) -> Result<Album, SmugMugError> {
         let children_uri = self.uris.child_nodes.as_ref().unwrap(); //Should always be true right?
-        let req_url = url::Url::parse(API_ORIGIN)?.join(children_uri)?;
+        let req_url = client.api_url(children_uri)?;
         let params = vec![("_verbosity", "1")];
 
-        let mut album_props: serde_json::Value = serde_json::to_value(&album_props)?;
-        album_props
+        let mut props: serde_json::Value = serde_json::to_value(&album_props)?;
+        props
             .as_object_mut()
             .ok_or(SmugMugError::JsonSerialization(
                 "Album Props is not a JSON object".to_string(),
             ))?
             .insert("Type".to_string(), json!("Album"));
-        let data = serde_json::to_vec(&album_props)?;
+        let data = serde_json::to_vec(&props)?;
 
-        let node = client
+        let resp = client
             .post::<NodeResponse>(req_url.as_str(), data, Some(&params))
-            .await?
+            .await?;
+        if client.is_dry_run() {
+            return Ok(Album::from_dry_run_props(client, album_props));
+        }
+        let node = resp
             .payload
             .ok_or(SmugMugError::ResponseMissing())
             .map(|mut v| {
@@ -205,7 +204,7 @@ impl Node {
         filter_by_type: NodeTypeFilters,
         sort_direction: SortDirection,
         sort_method: SortMethod,
-    ) -> Result<impl Stream<Item=Result<Node, SmugMugError>>, SmugMugError> {
+    ) -> Result<impl Stream<Item = Result<Node, SmugMugError>>, SmugMugError> {
         self.children_with_client(
             self.client
                 .as_ref()
@@ -224,7 +223,7 @@ impl Node {
         filter_by_type: NodeTypeFilters,
         sort_direction: SortDirection,
         sort_method: SortMethod,
-    ) -> Result<impl Stream<Item=Result<Node, SmugMugError>>, SmugMugError> {
+    ) -> Result<impl Stream<Item = Result<Node, SmugMugError>>, SmugMugError> {
         // Build up the query parameters
         let mut params: Vec<(&str, &str)> = vec![("SortDirection", sort_direction.into())];
         match filter_by_type {
@@ -245,6 +244,67 @@ impl Node {
             nodes
         ))
     }
+
+    /// Retrieves the child nodes information of this node, stopping once the token is cancelled.
+    /// The nodes returned before then are kept and the stream's
+    /// [`CancellableStream::resume_position`] can be passed as `resume_from` to continue the
+    /// listing later.
+    pub fn children_cancellable(
+        &self,
+        filter_by_type: NodeTypeFilters,
+        sort_direction: SortDirection,
+        sort_method: SortMethod,
+        cancel: CancellationToken,
+        resume_from: Option<PagePosition>,
+    ) -> Result<CancellableStream<'_, Node>, SmugMugError> {
+        self.children_cancellable_with_client(
+            self.client
+                .as_ref()
+                .ok_or(SmugMugError::ClientNotFound())?
+                .clone(),
+            filter_by_type,
+            sort_direction,
+            sort_method,
+            cancel,
+            resume_from,
+        )
+    }
+
+    /// Retrieves the child nodes information of this node using the provided client, stopping
+    /// once the token is cancelled
+    pub fn children_cancellable_with_client(
+        &self,
+        client: Client,
+        filter_by_type: NodeTypeFilters,
+        sort_direction: SortDirection,
+        sort_method: SortMethod,
+        cancel: CancellationToken,
+        resume_from: Option<PagePosition>,
+    ) -> Result<CancellableStream<'_, Node>, SmugMugError> {
+        let mut params: Vec<(&str, &str)> = vec![("SortDirection", sort_direction.into())];
+        match filter_by_type {
+            NodeTypeFilters::Any => (),
+            _ => params.push(("Type", filter_by_type.into())),
+        };
+
+        match sort_method {
+            SortMethod::SortIndex => (),
+            _ => params.push(("SortMethod", sort_method.into())),
+        }
+
+        let position = PositionTracker::default();
+        let stream = stream_children_from_url!(
+            client,
+            self.uris.child_nodes.as_ref(),
+            &params,
+            NodesResponse,
+            nodes,
+            resume_from,
+            Some(cancel),
+            position.clone()
+        );
+        Ok(CancellableStream::new(stream, position))
+    }
 }
 
 impl PartialEq for Node {
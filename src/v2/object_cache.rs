@@ -0,0 +1,211 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const API_PATH_PREFIX: &str = "/api/v2/";
+
+/// Kind of object a cached response holds, determined from the request path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachedObjectKind {
+    /// `/api/v2/user/...`
+    User,
+    /// `/api/v2/node/...`
+    Node,
+    /// `/api/v2/album/...`
+    Album,
+    /// `/api/v2/image/...`
+    Image,
+    /// Anything else
+    Other,
+}
+
+impl CachedObjectKind {
+    fn from_path(path: &str) -> Self {
+        let kind = path
+            .strip_prefix(API_PATH_PREFIX)
+            .and_then(|v| v.split(['/', '!']).next());
+        match kind {
+            Some("user") => Self::User,
+            Some("node") => Self::Node,
+            Some("album") => Self::Album,
+            Some("image") => Self::Image,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// In-memory LRU cache of GET responses used by the [`crate::v2::Client`] so that frequently
+/// requested objects are served without a request.
+///
+/// Entries are keyed by the full request url including the parameters and expire after the TTL
+/// configured for their [`CachedObjectKind`].  A PATCH, POST, PUT or DELETE made through the
+/// client invalidates the cached entries for the same uri, so updating an album or node is seen
+/// by the next read.  The cache is enabled with [`crate::v2::ClientBuilder::object_cache`].
+///
+/// Responses served from the cache don't have rate limit information.
+///
+/// ```rust
+/// use smugmug::v2::{CachedObjectKind, Client, ObjectCache};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let cache = Arc::new(
+///     ObjectCache::new(1000)
+///         .with_default_ttl(Duration::from_secs(30))
+///         .with_ttl(CachedObjectKind::Image, Duration::from_secs(300)),
+/// );
+/// let client = Client::builder().object_cache(cache.clone()).build().unwrap();
+///
+/// // Drop anything cached for an album changed outside this client
+/// cache.invalidate("/api/v2/album/SJT3DX");
+/// ```
+#[derive(Debug)]
+pub struct ObjectCache {
+    capacity: usize,
+    default_ttl: Duration,
+    ttls: HashMap<CachedObjectKind, Duration>,
+    entries: Mutex<CacheEntries>,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    entries: HashMap<String, CacheEntry>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    path: String,
+    body: Bytes,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+impl ObjectCache {
+    /// Creates a cache holding up to `capacity` responses with a default TTL of 60 seconds
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            default_ttl: Duration::from_secs(60),
+            ttls: HashMap::new(),
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// TTL used for kinds of objects without their own TTL
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// TTL used for the given kind of object
+    pub fn with_ttl(mut self, kind: CachedObjectKind, ttl: Duration) -> Self {
+        self.ttls.insert(kind, ttl);
+        self
+    }
+
+    /// Removes the cached entries for the uri, which can be a path or a full url.  Multi-get
+    /// entries including the object are removed as well.
+    pub fn invalidate(&self, uri: &str) {
+        let path = url::Url::parse(uri)
+            .map(|v| v.path().to_string())
+            .unwrap_or_else(|_| uri.split('?').next().unwrap_or(uri).to_string());
+        let mut entries = self.lock();
+        entries
+            .entries
+            .retain(|_, entry| !Self::path_includes(&entry.path, &path));
+    }
+
+    /// Removes all cached entries
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    /// Number of cached entries including any that have expired but not been removed yet
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns true if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Returns the cached body for the url if it hasn't expired
+    pub(crate) fn get(&self, url: &url::Url) -> Option<Bytes> {
+        let mut entries = self.lock();
+        entries.tick += 1;
+        let tick = entries.tick;
+        let entry = entries.entries.get_mut(url.as_str())?;
+        if entry.expires_at <= Instant::now() {
+            entries.entries.remove(url.as_str());
+            return None;
+        }
+        entry.last_used = tick;
+        Some(entry.body.clone())
+    }
+
+    // Caches the body for the url evicting the least recently used entry if full
+    pub(crate) fn put(&self, url: &url::Url, body: Bytes) {
+        let ttl = self
+            .ttls
+            .get(&CachedObjectKind::from_path(url.path()))
+            .copied()
+            .unwrap_or(self.default_ttl);
+        if ttl.is_zero() {
+            return;
+        }
+
+        let mut entries = self.lock();
+        entries.tick += 1;
+        let tick = entries.tick;
+        if entries.entries.len() >= self.capacity && !entries.entries.contains_key(url.as_str()) {
+            let lru = entries
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                entries.entries.remove(&lru);
+            }
+        }
+        entries.entries.insert(
+            url.to_string(),
+            CacheEntry {
+                path: url.path().to_string(),
+                body,
+                expires_at: Instant::now() + ttl,
+                last_used: tick,
+            },
+        );
+    }
+
+    // Determines if the cached path holds the object at the given path.  This covers the
+    // object itself along with multi-get paths such as `/api/v2/album/a,b,c`.
+    fn path_includes(cached_path: &str, path: &str) -> bool {
+        if cached_path == path {
+            return true;
+        }
+        match path.rsplit_once('/') {
+            Some((prefix, id)) if !id.is_empty() => cached_path
+                .strip_prefix(prefix)
+                .and_then(|v| v.strip_prefix('/'))
+                .is_some_and(|ids| ids.split(',').any(|v| v == id)),
+            _ => false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheEntries> {
+        self.entries
+            .lock()
+            .expect("Failed locking the object cache")
+    }
+}
@@ -0,0 +1,229 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::RateLimitWindow;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Storage for the latest [`RateLimitWindow`] returned from the API.
+///
+/// The [`crate::v2::Client`] records every rate limit window it receives into its store and the
+/// [`crate::v2::ThrottlePolicy`] reads it back before each request.  Sharing a store between
+/// clients (or processes) lets them coordinate on one rate limit budget.
+///
+/// ```rust
+/// use smugmug::v2::{Client, Creds, InMemoryRateLimitStore};
+/// use std::sync::Arc;
+///
+/// let store = Arc::new(InMemoryRateLimitStore::default());
+/// let client_a = Client::builder()
+///     .creds(Creds::from_tokens("api_key", None, None, None))
+///     .rate_limit_store(store.clone())
+///     .build()
+///     .unwrap();
+/// let client_b = Client::builder()
+///     .creds(Creds::from_tokens("api_key", None, None, None))
+///     .rate_limit_store(store)
+///     .build()
+///     .unwrap();
+/// ```
+pub trait RateLimitStore: Send + Sync {
+    /// Retrieves the latest recorded rate limit window if there is one
+    fn load(&self) -> Option<Arc<RateLimitWindow>>;
+
+    /// Records the rate limit window.  Implementations should ignore windows that are older than
+    /// the one already recorded.
+    fn store(&self, window: Arc<RateLimitWindow>);
+}
+
+/// Rate limit store held in memory.  This is the default store used by each client.
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+    window: RwLock<Option<Arc<RateLimitWindow>>>,
+}
+
+impl RateLimitStore for InMemoryRateLimitStore {
+    fn load(&self) -> Option<Arc<RateLimitWindow>> {
+        self.window
+            .read()
+            .expect("Failed read locking for last rate window update")
+            .clone()
+    }
+
+    fn store(&self, window: Arc<RateLimitWindow>) {
+        let mut current = self
+            .window
+            .write()
+            .expect("Failed write locking for last rate window update");
+        if current
+            .as_ref()
+            .is_none_or(|v| v.timestamp() <= window.timestamp())
+        {
+            *current = Some(window);
+        }
+    }
+}
+
+/// Rate limit store kept in a file and guarded with a file lock so that multiple processes using
+/// the same API key can coordinate on one rate limit budget.
+///
+/// The latest window is kept in memory so requests never wait on the file.  Stored windows are
+/// written to the file on tokio's blocking thread pool and the file is re-read in the background
+/// at most once per [`Self::with_refresh_interval`] to pick up windows from other processes.
+/// Outside of a tokio runtime the file is read and written on the calling thread instead.
+///
+/// Failures reading or writing the file are logged and otherwise ignored since the rate limit
+/// information is advisory.
+#[derive(Debug, Clone)]
+pub struct FileRateLimitStore {
+    path: PathBuf,
+    refresh_interval: Duration,
+    cached: Arc<Mutex<CachedWindow>>,
+}
+
+#[derive(Debug, Default)]
+struct CachedWindow {
+    window: Option<Arc<RateLimitWindow>>,
+    refreshed: Option<Instant>,
+    refreshing: bool,
+}
+
+impl CachedWindow {
+    // Keeps the window if it is newer than the one held
+    fn update(&mut self, window: Arc<RateLimitWindow>) {
+        if self
+            .window
+            .as_ref()
+            .is_none_or(|v| v.timestamp() <= window.timestamp())
+        {
+            self.window = Some(window);
+        }
+    }
+}
+
+impl FileRateLimitStore {
+    /// Creates a store backed by the file at the given path.  The file is created if missing.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            refresh_interval: Duration::from_secs(1),
+            cached: Arc::default(),
+        }
+    }
+
+    /// How often the file is re-read for windows stored by other processes.  Defaults to 1
+    /// second.
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CachedWindow> {
+        self.cached
+            .lock()
+            .expect("Failed locking the cached rate limit window")
+    }
+
+    // Runs the file access on the blocking thread pool when within a tokio runtime
+    fn run_blocking(f: impl FnOnce() + Send + 'static) {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(f);
+            }
+            Err(_) => f(),
+        }
+    }
+
+    // Re-reads the file into the cached window
+    fn refresh(&self) {
+        let window = self
+            .try_load()
+            .inspect_err(|err| {
+                log::warn!("Failed reading rate limit file {:?}: {err}", self.path);
+            })
+            .ok()
+            .flatten();
+        let mut cached = self.lock();
+        if let Some(window) = window {
+            cached.update(Arc::new(window));
+        }
+        cached.refreshed = Some(Instant::now());
+        cached.refreshing = false;
+    }
+
+    fn open(&self) -> std::io::Result<File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+    }
+
+    fn read_window(file: &mut File) -> std::io::Result<Option<RateLimitWindow>> {
+        let mut contents = String::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_string(&mut contents)?;
+        if contents.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    fn try_load(&self) -> std::io::Result<Option<RateLimitWindow>> {
+        let mut file = self.open()?;
+        file.lock_shared()?;
+        Self::read_window(&mut file)
+    }
+
+    fn try_store(&self, window: &RateLimitWindow) -> std::io::Result<()> {
+        let mut file = self.open()?;
+        file.lock()?;
+        let is_newer = Self::read_window(&mut file)
+            .ok()
+            .flatten()
+            .is_none_or(|v| v.timestamp() <= window.timestamp());
+        if is_newer {
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(&serde_json::to_vec(window)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl RateLimitStore for FileRateLimitStore {
+    fn load(&self) -> Option<Arc<RateLimitWindow>> {
+        let needs_refresh = {
+            let mut cached = self.lock();
+            let is_stale = cached
+                .refreshed
+                .is_none_or(|v| v.elapsed() >= self.refresh_interval);
+            let needs_refresh = is_stale && !cached.refreshing;
+            cached.refreshing |= needs_refresh;
+            needs_refresh
+        };
+        if needs_refresh {
+            let store = self.clone();
+            Self::run_blocking(move || store.refresh());
+        }
+        self.lock().window.clone()
+    }
+
+    fn store(&self, window: Arc<RateLimitWindow>) {
+        self.lock().update(window.clone());
+        let store = self.clone();
+        Self::run_blocking(move || {
+            if let Err(err) = store.try_store(&window) {
+                log::warn!("Failed writing rate limit file {:?}: {err}", store.path);
+            }
+        });
+    }
+}
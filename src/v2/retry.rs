@@ -0,0 +1,186 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::errors::SmugMugError;
+use http::{Method, StatusCode};
+use rand::Rng;
+use std::time::Duration;
+
+/// Policy used by the [`crate::v2::Client`] for retrying requests that failed with a
+/// `429 Too Many Requests`, a `5xx` server error or a connection/timeout error.
+///
+/// Retries are opt-in and are enabled with [`crate::v2::ClientBuilder::retry_policy`].  The
+/// backoff is waited out with [`crate::v2::HttpTransport::sleep`].
+///
+/// ```rust
+/// use smugmug::v2::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(5)
+///     .with_initial_backoff(Duration::from_millis(250))
+///     .with_max_backoff(Duration::from_secs(10));
+/// assert_eq!(policy.max_attempts(), 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    use_jitter: bool,
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            use_jitter: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy allowing up to `max_attempts` attempts (including the first) per request
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Default::default()
+        }
+    }
+
+    /// Backoff used before the first retry.  This is doubled for each following retry.
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper limit for the backoff between retries
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Randomizes the backoff between half and the full computed backoff.  Defaults to true.
+    pub fn with_jitter(mut self, use_jitter: bool) -> Self {
+        self.use_jitter = use_jitter;
+        self
+    }
+
+    /// Allows POST requests to be retried on server and connection errors.  Defaults to false
+    /// since the request may have been processed.  A 429 is always retried.
+    pub fn with_retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Maximum number of attempts made for a request
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    // Backoff to wait before the given retry attempt (1 based)
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.use_jitter && !backoff.is_zero() {
+            rand::rng().random_range(backoff / 2..=backoff)
+        } else {
+            backoff
+        }
+    }
+
+    // Determines if a response with the given status should be retried
+    pub(crate) fn is_retryable_status(&self, method: &Method, status: StatusCode) -> bool {
+        status == StatusCode::TOO_MANY_REQUESTS
+            || (status.is_server_error() && self.is_retryable_method(method))
+    }
+
+    // Determines if a failed request (no response) should be retried
+    pub(crate) fn is_retryable_error(&self, method: &Method, err: &SmugMugError) -> bool {
+        let is_transient = match err {
+            SmugMugError::Request(err) => err.is_timeout() || err.is_connect(),
+            SmugMugError::Io(_) => true,
+            _ => false,
+        };
+        is_transient && self.is_retryable_method(method)
+    }
+
+    fn is_retryable_method(&self, method: &Method) -> bool {
+        self.retry_non_idempotent || *method != Method::POST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_max() {
+        let policy = RetryPolicy::new(10)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500))
+            .with_jitter(false);
+        let backoffs: Vec<u128> = (1..=5).map(|v| policy.backoff(v).as_millis()).collect();
+        assert_eq!(backoffs, [100, 200, 400, 500, 500]);
+
+        // Large retry counts saturate rather than overflow
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn jitter_stays_between_half_and_full_backoff() {
+        let policy = RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(100));
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff >= Duration::from_millis(100));
+            assert!(backoff <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn max_attempts_is_at_least_one() {
+        assert_eq!(RetryPolicy::new(0).max_attempts(), 1);
+        assert_eq!(RetryPolicy::default().max_attempts(), 3);
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable_status(&Method::GET, StatusCode::TOO_MANY_REQUESTS));
+        assert!(policy.is_retryable_status(&Method::GET, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.is_retryable_status(&Method::PATCH, StatusCode::BAD_GATEWAY));
+        assert!(!policy.is_retryable_status(&Method::GET, StatusCode::NOT_FOUND));
+        assert!(!policy.is_retryable_status(&Method::GET, StatusCode::OK));
+    }
+
+    #[test]
+    fn post_only_retried_on_too_many_requests_unless_allowed() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable_status(&Method::POST, StatusCode::TOO_MANY_REQUESTS));
+        assert!(!policy.is_retryable_status(&Method::POST, StatusCode::SERVICE_UNAVAILABLE));
+        let io_error = SmugMugError::Io(std::io::Error::other("reset"));
+        assert!(!policy.is_retryable_error(&Method::POST, &io_error));
+        assert!(policy.is_retryable_error(&Method::GET, &io_error));
+
+        let policy = policy.with_retry_non_idempotent(true);
+        assert!(policy.is_retryable_status(&Method::POST, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.is_retryable_error(&Method::POST, &io_error));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let policy = RetryPolicy::default();
+        let err = SmugMugError::HttpStatus(404, "https://api.smugmug.com".to_string());
+        assert!(!policy.is_retryable_error(&Method::GET, &err));
+        assert!(!policy.is_retryable_error(&Method::GET, &SmugMugError::ResponseMissing()));
+    }
+}
@@ -0,0 +1,188 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::RateLimitWindow;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Policy used by the [`crate::v2::Client`] to slow down or pause outgoing requests based on the
+/// last [`RateLimitWindow`] returned from the API.
+///
+/// When the number of remaining requests drops below [`Self::with_slow_down_below`] requests are
+/// spread out evenly over the time left in the current window.  When it drops to
+/// [`Self::with_pause_at`] requests are paused until the window resets.
+///
+/// Throttling is opt-in and is enabled with [`crate::v2::ClientBuilder::throttle_policy`].  The
+/// delays are waited out with [`crate::v2::HttpTransport::sleep`].
+///
+/// ```rust
+/// use smugmug::v2::ThrottlePolicy;
+/// use std::time::Duration;
+///
+/// let policy = ThrottlePolicy::default()
+///     .with_slow_down_below(50)
+///     .with_pause_at(2)
+///     .with_max_slow_down_delay(Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThrottlePolicy {
+    pause_at: u64,
+    slow_down_below: u64,
+    max_slow_down_delay: Duration,
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            pause_at: 5,
+            slow_down_below: 100,
+            max_slow_down_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ThrottlePolicy {
+    /// Pause requests until the window resets once the remaining requests is at or below this
+    pub fn with_pause_at(mut self, pause_at: u64) -> Self {
+        self.pause_at = pause_at;
+        self
+    }
+
+    /// Start spacing out requests once the remaining requests is below this
+    pub fn with_slow_down_below(mut self, slow_down_below: u64) -> Self {
+        self.slow_down_below = slow_down_below;
+        self
+    }
+
+    /// Upper limit for the delay added between requests while slowing down
+    pub fn with_max_slow_down_delay(mut self, delay: Duration) -> Self {
+        self.max_slow_down_delay = delay;
+        self
+    }
+
+    // Determines how long to wait before sending the next request
+    pub(crate) fn delay_for(
+        &self,
+        window: &RateLimitWindow,
+        now: DateTime<Utc>,
+    ) -> Option<Duration> {
+        // If the API told us to back off then wait until it says we can resume
+        if let Some(delay) = window
+            .resume_after()
+            .and_then(|v| (v - now).to_std().ok())
+            .filter(|v| !v.is_zero())
+        {
+            return Some(delay);
+        }
+
+        let remaining = window.num_remaining_requests()?;
+        let until_reset = window
+            .window_reset_datetime()
+            .and_then(|v| (v - now).to_std().ok())
+            .filter(|v| !v.is_zero())?;
+
+        if remaining <= self.pause_at {
+            Some(until_reset)
+        } else if remaining < self.slow_down_below {
+            let spread = until_reset / u32::try_from(remaining - self.pause_at).unwrap_or(u32::MAX);
+            Some(spread.min(self.max_slow_down_delay))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use http::HeaderMap;
+
+    fn now() -> DateTime<Utc> {
+        Utc.timestamp_opt(1_700_000_000, 0).unwrap()
+    }
+
+    // Window as returned at `now` with the given headers
+    fn window(headers: &[(&'static str, String)]) -> RateLimitWindow {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, value.parse().unwrap());
+        }
+        RateLimitWindow::from_headers(now(), &map)
+    }
+
+    fn window_with(remaining: u64, reset_in_secs: i64) -> RateLimitWindow {
+        window(&[
+            ("x-ratelimit-remaining", remaining.to_string()),
+            (
+                "x-ratelimit-reset",
+                (now().timestamp() + reset_in_secs).to_string(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn no_delay_with_plenty_remaining() {
+        let policy = ThrottlePolicy::default();
+        assert_eq!(policy.delay_for(&window_with(500, 100), now()), None);
+        assert_eq!(policy.delay_for(&window_with(100, 100), now()), None);
+    }
+
+    #[test]
+    fn low_remaining_spreads_requests_over_the_window() {
+        let policy = ThrottlePolicy::default();
+        // 40 requests left before pausing over 100 seconds
+        assert_eq!(
+            policy.delay_for(&window_with(45, 100), now()),
+            Some(Duration::from_millis(2500))
+        );
+
+        // Capped by the max slow down delay
+        assert_eq!(
+            policy.delay_for(&window_with(6, 100), now()),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn exhausted_remaining_pauses_until_reset() {
+        let policy = ThrottlePolicy::default().with_pause_at(2);
+        assert_eq!(
+            policy.delay_for(&window_with(2, 100), now()),
+            Some(Duration::from_secs(100))
+        );
+        assert_eq!(
+            policy.delay_for(&window_with(0, 100), now()),
+            Some(Duration::from_secs(100))
+        );
+    }
+
+    #[test]
+    fn expired_window_is_ignored() {
+        let policy = ThrottlePolicy::default();
+        assert_eq!(policy.delay_for(&window_with(0, -10), now()), None);
+        assert_eq!(policy.delay_for(&window_with(0, 0), now()), None);
+        assert_eq!(
+            policy.delay_for(&window_with(0, 100), now() + chrono::Duration::seconds(200)),
+            None
+        );
+    }
+
+    #[test]
+    fn retry_after_waits_until_resume() {
+        let policy = ThrottlePolicy::default();
+        let window = window(&[("retry-after", "30".to_string())]);
+        assert_eq!(
+            policy.delay_for(&window, now()),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            policy.delay_for(&window, now() + chrono::Duration::seconds(30)),
+            None
+        );
+    }
+}
@@ -0,0 +1,201 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+use crate::v2::errors::SmugMugError;
+use bytes::{Bytes, BytesMut};
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use http::{HeaderMap, Method, StatusCode};
+use std::time::Duration;
+
+/// Stream of body chunks returned from a [`HttpTransport`]
+pub type HttpBodyStream = BoxStream<'static, Result<Bytes, SmugMugError>>;
+
+/// Transport used by the [`crate::v2::Client`] for sending requests.
+///
+/// The request handed to the transport has already been signed so implementations only need
+/// to send it and return the status, headers and body.  The default implementation is
+/// [`ReqwestTransport`].  A custom transport is set with
+/// [`crate::v2::ClientBuilder::transport`], which allows running on alternative runtimes or
+/// serving canned responses from memory in tests.
+///
+/// The waits between retry attempts and while throttling go through [`HttpTransport::sleep`],
+/// which uses a Tokio timer unless overridden.
+///
+/// ```rust
+/// use futures::future::BoxFuture;
+/// use smugmug::v2::{HttpRequest, HttpResponse, HttpTransport, SmugMugError};
+///
+/// // Responds to every request with the same JSON body
+/// struct CannedTransport(&'static str);
+///
+/// impl HttpTransport for CannedTransport {
+///     fn send(&self, _req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+///         let body = self.0;
+///         Box::pin(async move {
+///             Ok(HttpResponse::from_bytes(
+///                 http::StatusCode::OK,
+///                 http::HeaderMap::new(),
+///                 body.into(),
+///             ))
+///         })
+///     }
+/// }
+/// ```
+pub trait HttpTransport: Send + Sync {
+    /// Sends the request returning the response
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>>;
+
+    /// Waits for the given duration.  Transports running outside a Tokio runtime should
+    /// override this with their runtime's timer.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Signed request to be sent by a [`HttpTransport`]
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// HTTP method of the request
+    pub method: Method,
+    /// Full url including the query parameters
+    pub url: url::Url,
+    /// Request headers including the Authorization header if the request was signed
+    pub headers: HeaderMap,
+    /// Request body if there is one
+    pub body: Option<Bytes>,
+    /// Total time allowed for the request
+    pub timeout: Option<Duration>,
+}
+
+impl HttpRequest {
+    /// Creates a request without headers or a body
+    pub fn new(method: Method, url: url::Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: None,
+        }
+    }
+}
+
+/// Response returned by a [`HttpTransport`]
+pub struct HttpResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: HttpBodyStream,
+}
+
+impl HttpResponse {
+    /// Creates a response with a streamed body
+    pub fn new(status: StatusCode, headers: HeaderMap, body: HttpBodyStream) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    /// Creates a response with the body already in memory
+    pub fn from_bytes(status: StatusCode, headers: HeaderMap, body: Bytes) -> Self {
+        Self::new(
+            status,
+            headers,
+            futures::stream::once(async move { Ok(body) }).boxed(),
+        )
+    }
+
+    /// HTTP status of the response
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Response headers
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Mutable access to the response headers
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// Reads the full body into memory
+    pub async fn bytes(self) -> Result<Bytes, SmugMugError> {
+        collect_body(self.body).await
+    }
+
+    /// Converts the response into the stream of body chunks
+    pub fn into_body_stream(self) -> HttpBodyStream {
+        self.body
+    }
+
+    // Replaces the body with one wrapping it
+    pub(crate) fn map_body(self, f: impl FnOnce(HttpBodyStream) -> HttpBodyStream) -> Self {
+        Self {
+            body: f(self.body),
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Debug for HttpResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+// Reads the body stream into memory
+pub(crate) async fn collect_body(mut stream: HttpBodyStream) -> Result<Bytes, SmugMugError> {
+    let mut body = BytesMut::new();
+    while let Some(chunk) = stream.try_next().await? {
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+/// [`HttpTransport`] implemented with [`reqwest::Client`].  This is the default transport.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    https_client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Creates a transport using the given pre-configured client
+    pub fn new(https_client: reqwest::Client) -> Self {
+        Self { https_client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+        Box::pin(async move {
+            let mut builder = self
+                .https_client
+                .request(req.method, req.url)
+                .headers(req.headers);
+            if let Some(timeout) = req.timeout {
+                builder = builder.timeout(timeout);
+            }
+            if let Some(body) = req.body {
+                builder = builder.body(body);
+            }
+            let resp = builder.send().await?;
+            Ok(HttpResponse::new(
+                resp.status(),
+                resp.headers().clone(),
+                resp.bytes_stream().map_err(SmugMugError::from).boxed(),
+            ))
+        })
+    }
+}
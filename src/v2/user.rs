@@ -8,7 +8,7 @@
 use crate::v2::errors::SmugMugError;
 use crate::v2::macros::obj_from_url;
 use crate::v2::parsers::is_none_or_empty_str;
-use crate::v2::{API_ORIGIN, Client, Node};
+use crate::v2::{Client, Node};
 use serde::{Deserialize, Serialize};
 
 /// Holds information returned from the User API.
@@ -58,15 +58,13 @@ impl User {
 
     /// Returns information for the specified user id
     pub async fn from_id(client: Client, id: &str) -> Result<User, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?
-            .join(Self::BASE_URI)?
-            .join(id)?;
+        let req_url = client.api_url(Self::BASE_URI)?.join(id)?;
         Self::from_url(client, req_url.as_str()).await
     }
 
     /// Returns information for the authenticated user
     pub async fn authenticated_user_info(client: Client) -> Result<User, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?.join("/api/v2!authuser")?;
+        let req_url = client.api_url("/api/v2!authuser")?;
         Self::from_url(client, req_url.as_str()).await
     }
 
@@ -75,13 +73,10 @@ impl User {
     /// NOTE: if this object was deserialized externally this will fail as the internal client
     /// isn't valid
     pub async fn node(self) -> Result<Node, SmugMugError> {
-        let req_url = url::Url::parse(API_ORIGIN)?.join(self.uris.node.as_str())?;
+        let client = self.client.ok_or(SmugMugError::ClientNotFound())?;
+        let req_url = client.api_url(self.uris.node.as_str())?;
 
-        Node::from_url(
-            self.client.ok_or(SmugMugError::ClientNotFound())?.clone(),
-            req_url.as_str(),
-        )
-        .await
+        Node::from_url(client, req_url.as_str()).await
     }
 }
 
@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+#![cfg(feature = "blocking")]
+
+mod helpers;
+
+#[cfg(test)]
+mod test {
+    use crate::helpers::{
+        CannedResponse, MemoryTransport, get_transport_client, node_value, response_json, user_json,
+    };
+    use serde_json::json;
+    use smugmug::v2::blocking::{Client, Node, User};
+    use smugmug::v2::{NodeTypeFilters, SmugMugError, SortDirection, SortMethod};
+
+    fn ok(body: Vec<u8>) -> CannedResponse {
+        CannedResponse {
+            status: 200,
+            headers: vec![],
+            body,
+        }
+    }
+
+    #[test]
+    fn node_children_iterated_across_pages() {
+        let transport = MemoryTransport::new(vec![
+            ok(user_json("apidemo")),
+            ok(response_json(
+                json!({ "Node": node_value("root", "root", "Folder") }),
+            )),
+            ok(response_json(json!({
+                "Node": [node_value("a1", "first", "Album")],
+                "Pages": { "NextPage": "/api/v2/node/root!children?start=2&count=1" }
+            }))),
+            ok(response_json(json!({
+                "Node": [node_value("a2", "second", "Album")],
+                "Pages": {}
+            }))),
+        ]);
+        let client =
+            Client::from_async(get_transport_client(transport.clone()).build().unwrap()).unwrap();
+
+        let root_node = User::from_id(&client, "apidemo").unwrap().node().unwrap();
+        assert_eq!(root_node.node_id, "root");
+
+        let names = root_node
+            .children(
+                NodeTypeFilters::Album,
+                SortDirection::Ascending,
+                SortMethod::Name,
+            )
+            .unwrap()
+            .map(|v| v.unwrap().name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["first", "second"]);
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[3].url.as_str().contains("start=2"));
+    }
+
+    // Responses for the root node followed by a first page of children pointing at a second
+    fn root_node_first_page() -> Vec<CannedResponse> {
+        vec![
+            ok(response_json(
+                json!({ "Node": node_value("root", "root", "Folder") }),
+            )),
+            ok(response_json(json!({
+                "Node": [node_value("a1", "first", "Album")],
+                "Pages": { "NextPage": "/api/v2/node/root!children?start=2&count=1" }
+            }))),
+        ]
+    }
+
+    fn album_children(node: &Node) -> impl Iterator<Item = Result<Node, SmugMugError>> + '_ {
+        node.children(
+            NodeTypeFilters::Album,
+            SortDirection::Ascending,
+            SortMethod::Name,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn http_error_returned_from_blocking_call() {
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 404,
+            headers: vec![],
+            body: vec![],
+        }]);
+        let client = Client::from_async(get_transport_client(transport).build().unwrap()).unwrap();
+
+        let result = User::from_id(&client, "missing");
+        assert!(matches!(result, Err(SmugMugError::HttpStatus(404, _))));
+    }
+
+    #[test]
+    fn error_fetching_later_page_returned_from_iterator() {
+        let mut responses = root_node_first_page();
+        responses.push(CannedResponse {
+            status: 500,
+            headers: vec![],
+            body: vec![],
+        });
+        let transport = MemoryTransport::new(responses);
+        let client = Client::from_async(get_transport_client(transport).build().unwrap()).unwrap();
+
+        let root_node = Node::from_id(&client, "root").unwrap();
+        let mut children = album_children(&root_node);
+        assert_eq!(children.next().unwrap().unwrap().name, "first");
+        assert!(matches!(
+            children.next(),
+            Some(Err(SmugMugError::HttpStatus(500, _)))
+        ));
+    }
+
+    #[test]
+    fn partially_read_iterator_dropped_outside_runtime() {
+        let transport = MemoryTransport::new(root_node_first_page());
+        let thread_transport = transport.clone();
+        std::thread::spawn(move || {
+            let client =
+                Client::from_async(get_transport_client(thread_transport).build().unwrap())
+                    .unwrap();
+            let root_node = Node::from_id(&client, "root").unwrap();
+            let mut children = album_children(&root_node);
+            assert_eq!(children.next().unwrap().unwrap().name, "first");
+            drop(children);
+            drop(root_node);
+            drop(client);
+        })
+        .join()
+        .expect("Dropping the blocking client panicked");
+
+        // The second page was never requested
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+}
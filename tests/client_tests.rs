@@ -0,0 +1,1674 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+
+mod helpers;
+
+#[cfg(test)]
+mod test {
+    use crate::helpers::{
+        CannedResponse, MemoryTransport, get_transport_client, image_value, node_value,
+        response_json, user_json,
+    };
+    use bytes::Bytes;
+    use chrono::Utc;
+    use futures::StreamExt;
+    use futures::future::BoxFuture;
+    use smugmug::v2::{
+        AccountRegistry, ApiParams, CachedResponse, CancellationToken, CassetteMode, Client, Creds,
+        DiskHttpCache, DownloadOutcome, HttpBodyStream, HttpCache, HttpRequest, HttpResponse,
+        HttpTransport, Image, InMemoryHttpCache, Interceptor, Method, MutationJournal, Node,
+        NodeTypeFilters, ObjectCache, RequestCount, Response, RetryPolicy, SmugMugApi,
+        SmugMugError, SortDirection, SortMethod, ThrottlePolicy, User,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct SeenRequest {
+        url: String,
+        auth: Option<String>,
+        user_agent: Option<String>,
+    }
+
+    // Records the outgoing request and then fails it so nothing is sent
+    #[derive(Default)]
+    struct FaultInjector {
+        seen: Mutex<Vec<SeenRequest>>,
+    }
+
+    impl Interceptor for FaultInjector {
+        fn on_request(&self, req: &mut HttpRequest) -> Result<(), SmugMugError> {
+            let header = |name: &str| {
+                req.headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string())
+            };
+            self.seen.lock().unwrap().push(SeenRequest {
+                url: req.url.to_string(),
+                auth: header("Authorization"),
+                user_agent: header("User-Agent"),
+            });
+            Err(SmugMugError::ApiResponse(503, "Injected fault".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn interceptor_sees_signed_request_for_configured_origin() {
+        let injector = Arc::new(FaultInjector::default());
+        let client = Client::builder()
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .api_origin("http://localhost:9")
+            .user_agent("smugmug-tests")
+            .interceptor(injector.clone())
+            .build()
+            .unwrap();
+
+        let result = User::authenticated_user_info(client).await;
+        assert!(matches!(result, Err(SmugMugError::ApiResponse(503, _))));
+
+        let seen = injector.seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(
+            seen[0]
+                .url
+                .starts_with("http://localhost:9/api/v2!authuser")
+        );
+        assert!(seen[0].auth.as_ref().unwrap().starts_with("OAuth "));
+        assert_eq!(seen[0].user_agent.as_deref(), Some("smugmug-tests"));
+    }
+
+    // Records the status and request id of each response and overrides the remaining rate limit
+    #[derive(Default)]
+    struct ResponseRecorder {
+        seen: Mutex<Vec<(u16, Option<String>)>>,
+    }
+
+    impl Interceptor for ResponseRecorder {
+        fn on_response(&self, resp: &mut HttpResponse) -> Result<(), SmugMugError> {
+            let request_id = resp
+                .headers()
+                .get("x-request-id")
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string());
+            self.seen
+                .lock()
+                .unwrap()
+                .push((resp.status().as_u16(), request_id));
+            resp.headers_mut()
+                .insert("x-ratelimit-remaining", "7".parse().unwrap());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn interceptor_sees_response_status_and_headers() {
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 200,
+            headers: vec![("x-request-id", "req-1".to_string())],
+            body: user_json("apidemo"),
+        }]);
+        let recorder = Arc::new(ResponseRecorder::default());
+        let client = get_transport_client(transport)
+            .interceptor(recorder.clone())
+            .build()
+            .unwrap();
+
+        User::from_id(client.clone(), "apidemo").await.unwrap();
+
+        assert_eq!(
+            *recorder.seen.lock().unwrap(),
+            [(200, Some("req-1".to_string()))]
+        );
+        // Changes made by the interceptor are seen by the client
+        let window = client.get_last_rate_limit_window_update().unwrap();
+        assert_eq!(window.num_remaining_requests(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn interceptor_sees_each_retry_attempt_response() {
+        let failure = || CannedResponse {
+            status: 503,
+            headers: vec![],
+            body: vec![],
+        };
+        let transport = MemoryTransport::new(vec![
+            failure(),
+            failure(),
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: user_json("apidemo"),
+            },
+        ]);
+        let recorder = Arc::new(ResponseRecorder::default());
+        let client = get_transport_client(transport)
+            .retry_policy(RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(1)))
+            .interceptor(recorder.clone())
+            .build()
+            .unwrap();
+
+        User::from_id(client, "apidemo").await.unwrap();
+
+        let statuses: Vec<u16> = recorder.seen.lock().unwrap().iter().map(|v| v.0).collect();
+        assert_eq!(statuses, [503, 503, 200]);
+    }
+
+    #[tokio::test]
+    async fn http_status_error_omits_api_key() {
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 404,
+            headers: vec![],
+            body: vec![],
+        }]);
+        let client = get_transport_client(transport)
+            .creds(Creds::from_tokens("status-api-key", None, None, None))
+            .build()
+            .unwrap();
+
+        let err = User::from_id(client, "missing").await.unwrap_err();
+        assert!(matches!(err, SmugMugError::HttpStatus(404, _)));
+        for text in [err.to_string(), format!("{err:?}")] {
+            assert!(text.contains("APIKey=REDACTED"));
+            assert!(!text.contains("status-api-key"));
+        }
+    }
+
+    #[test]
+    fn origin_with_path_is_rejected() {
+        let result = Client::builder()
+            .api_origin("http://localhost:9/smugmug")
+            .build();
+        assert!(matches!(result, Err(SmugMugError::InvalidConfig(_))));
+
+        let result = Client::builder()
+            .upload_origin("http://localhost:9/?upload=1")
+            .build();
+        assert!(matches!(result, Err(SmugMugError::InvalidConfig(_))));
+
+        let client = Client::builder()
+            .api_origin("http://localhost:9/")
+            .build()
+            .unwrap();
+        assert_eq!(client.api_origin().as_str(), "http://localhost:9/");
+    }
+
+    #[tokio::test]
+    async fn transport_response_parsed_and_rate_limits_recorded() {
+        let reset = Utc::now().timestamp() + 600;
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 200,
+            headers: vec![
+                ("x-ratelimit-remaining", "42".to_string()),
+                ("x-ratelimit-reset", reset.to_string()),
+            ],
+            body: user_json("apidemo"),
+        }]);
+        let client = get_transport_client(transport.clone()).build().unwrap();
+
+        let user = User::from_id(client.clone(), "apidemo").await.unwrap();
+        assert_eq!(user.name, "apidemo");
+
+        let rate_limit = client.get_last_rate_limit_window_update().unwrap();
+        assert_eq!(rate_limit.num_remaining_requests(), Some(42));
+        assert_eq!(
+            rate_limit.window_reset_datetime().unwrap().timestamp(),
+            reset
+        );
+
+        let requests = transport.requests.lock().unwrap();
+        assert!(requests[0].url.as_str().contains("APIKey=key"));
+        assert!(requests[0].headers.get("Authorization").is_none());
+    }
+
+    #[tokio::test]
+    async fn retry_policy_retries_server_errors() {
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 503,
+                headers: vec![],
+                body: vec![],
+            },
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: user_json("apidemo"),
+            },
+        ]);
+        let client = get_transport_client(transport.clone())
+            .retry_policy(RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+
+        let user = User::from_id(client, "apidemo").await.unwrap();
+        assert_eq!(user.name, "apidemo");
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn retry_honors_retry_after_over_backoff() {
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 429,
+                headers: vec![("retry-after", "0".to_string())],
+                body: vec![],
+            },
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: user_json("apidemo"),
+            },
+        ]);
+        // The backoff alone would outlast the timeout
+        let client = get_transport_client(transport.clone())
+            .retry_policy(RetryPolicy::new(3).with_initial_backoff(Duration::from_secs(60)))
+            .build()
+            .unwrap();
+
+        let user = tokio::time::timeout(Duration::from_secs(5), User::from_id(client, "apidemo"))
+            .await
+            .expect("Retry-After wasn't honored")
+            .unwrap();
+        assert_eq!(user.name, "apidemo");
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+
+    // Serves the canned responses and records the waits instead of sleeping
+    struct RecordingSleepTransport {
+        inner: Arc<MemoryTransport>,
+        sleeps: Mutex<Vec<Duration>>,
+    }
+
+    impl HttpTransport for RecordingSleepTransport {
+        fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+            self.inner.send(req)
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            self.sleeps.lock().unwrap().push(duration);
+            Box::pin(futures::future::ready(()))
+        }
+    }
+
+    #[test]
+    fn retry_waits_through_the_transport_outside_tokio() {
+        let transport = Arc::new(RecordingSleepTransport {
+            inner: MemoryTransport::new(vec![
+                CannedResponse {
+                    status: 503,
+                    headers: vec![],
+                    body: vec![],
+                },
+                CannedResponse {
+                    status: 200,
+                    headers: vec![],
+                    body: user_json("apidemo"),
+                },
+            ]),
+            sleeps: Mutex::new(Vec::new()),
+        });
+        let client = get_transport_client(transport.clone())
+            .retry_policy(
+                RetryPolicy::new(3)
+                    .with_initial_backoff(Duration::from_secs(10))
+                    .with_jitter(false),
+            )
+            .build()
+            .unwrap();
+
+        let user = futures::executor::block_on(User::from_id(client, "apidemo")).unwrap();
+        assert_eq!(user.name, "apidemo");
+        assert_eq!(*transport.sleeps.lock().unwrap(), [Duration::from_secs(10)]);
+    }
+
+    #[test]
+    fn throttle_waits_through_the_transport_outside_tokio() {
+        let reset = (Utc::now().timestamp() + 600).to_string();
+        let transport = Arc::new(RecordingSleepTransport {
+            inner: MemoryTransport::new(
+                (0..2)
+                    .map(|_| CannedResponse {
+                        status: 200,
+                        headers: vec![
+                            ("x-ratelimit-remaining", "1".to_string()),
+                            ("x-ratelimit-reset", reset.clone()),
+                        ],
+                        body: user_json("apidemo"),
+                    })
+                    .collect(),
+            ),
+            sleeps: Mutex::new(Vec::new()),
+        });
+        let client = get_transport_client(transport.clone())
+            .throttle_policy(ThrottlePolicy::default())
+            .build()
+            .unwrap();
+
+        futures::executor::block_on(async {
+            User::from_id(client.clone(), "apidemo").await.unwrap();
+            User::from_id(client, "apidemo").await.unwrap();
+        });
+        let sleeps = transport.sleeps.lock().unwrap();
+        assert_eq!(sleeps.len(), 1);
+        assert!(sleeps[0] > Duration::from_secs(500) && sleeps[0] <= Duration::from_secs(600));
+    }
+
+    #[tokio::test]
+    async fn retry_stops_after_max_attempts() {
+        let transport = MemoryTransport::new(
+            (0..3)
+                .map(|_| CannedResponse {
+                    status: 502,
+                    headers: vec![],
+                    body: vec![],
+                })
+                .collect(),
+        );
+        let client = get_transport_client(transport.clone())
+            .retry_policy(RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+
+        let result = User::from_id(client, "apidemo").await;
+        assert!(matches!(result, Err(SmugMugError::HttpStatus(502, _))));
+        assert_eq!(transport.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn post_not_retried_on_server_error() {
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 503,
+            headers: vec![],
+            body: vec![],
+        }]);
+        let client = get_transport_client(transport.clone())
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .retry_policy(RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+
+        let result = client
+            .post::<serde_json::Value>(
+                "https://api.smugmug.com/api/v2/folder",
+                b"{}".to_vec(),
+                None,
+            )
+            .await;
+        assert!(matches!(result, Err(SmugMugError::HttpStatus(503, _))));
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn http_error_status_returned_without_retry_policy() {
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 503,
+            headers: vec![],
+            body: vec![],
+        }]);
+        let client = get_transport_client(transport).build().unwrap();
+
+        let result = User::from_id(client, "apidemo").await;
+        assert!(matches!(result, Err(SmugMugError::HttpStatus(503, _))));
+    }
+
+    #[tokio::test]
+    async fn put_delete_and_request_send_signed_requests() {
+        let ok = || CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: response_json(serde_json::json!({ "Album": { "Name": "updated" } })),
+        };
+        let transport = MemoryTransport::new(vec![ok(), ok(), ok()]);
+        let client = get_transport_client(transport.clone())
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .build()
+            .unwrap();
+        let url = "https://api.smugmug.com/api/v2/album/abc";
+
+        let resp = client
+            .put::<serde_json::Value>(url, br#"{"Name":"updated"}"#.to_vec(), None)
+            .await
+            .unwrap();
+        assert_eq!(resp.payload.unwrap()["Album"]["Name"], "updated");
+        client.delete::<serde_json::Value>(url, None).await.unwrap();
+        client
+            .request::<serde_json::Value>(
+                Method::PATCH,
+                url,
+                Some(br#"{"Title":"new"}"#.to_vec()),
+                Some(&[("_verbosity", "1")]),
+            )
+            .await
+            .unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        let expected = [
+            (Method::PUT, Some(&br#"{"Name":"updated"}"#[..])),
+            (Method::DELETE, None),
+            (Method::PATCH, Some(&br#"{"Title":"new"}"#[..])),
+        ];
+        assert_eq!(requests.len(), expected.len());
+        for (req, (method, body)) in requests.iter().zip(expected) {
+            assert_eq!(req.method, method);
+            assert_eq!(req.url.path(), "/api/v2/album/abc");
+            let auth = req.headers.get("Authorization").unwrap().to_str().unwrap();
+            assert!(auth.starts_with("OAuth "));
+            assert!(auth.contains("oauth_token=\"token\""));
+            assert!(auth.contains("oauth_signature="));
+            assert_eq!(req.body.as_deref(), body);
+            assert_eq!(req.headers.get("Content-Type").is_some(), body.is_some());
+        }
+        assert_eq!(requests[2].url.query(), Some("_verbosity=1"));
+    }
+
+    #[tokio::test]
+    async fn archive_streamed_to_writer_with_md5() {
+        let data = b"not really a jpeg".repeat(100);
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: response_json(serde_json::json!({ "Image": image_value("abc", &data) })),
+            },
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: data.clone(),
+            },
+        ]);
+        let client = get_transport_client(transport.clone()).build().unwrap();
+
+        let image = Image::from_id(client, "abc-0").await.unwrap();
+        let mut written: Vec<u8> = Vec::new();
+        let summary = image.download_archive_to(&mut written).await.unwrap();
+
+        assert_eq!(written, data);
+        assert_eq!(summary.size, image.archived_size.unwrap());
+        assert_eq!(&summary.md5, image.archived_md5.as_ref().unwrap());
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[1].url.host_str(), Some("photos.smugmug.com"));
+        assert_eq!(requests[1].url.path(), "/abc.jpg");
+    }
+
+    // Serves an image whose archive request succeeds without a body
+    struct MissingArchiveApi;
+
+    impl SmugMugApi for MissingArchiveApi {
+        fn request<'a>(
+            &'a self,
+            _method: Method,
+            _url: &'a str,
+            _data: Option<Vec<u8>>,
+            _params: Option<&'a ApiParams<'a>>,
+        ) -> BoxFuture<'a, Result<Response<serde_json::Value>, SmugMugError>> {
+            Box::pin(async {
+                Ok(Response {
+                    payload: Some(serde_json::json!({ "Image": image_value("abc", b"data") })),
+                    rate_limit: None,
+                })
+            })
+        }
+
+        fn get_binary_stream<'a>(
+            &'a self,
+            _url: &'a str,
+            _params: Option<&'a ApiParams<'a>>,
+        ) -> BoxFuture<'a, Result<Response<HttpBodyStream>, SmugMugError>> {
+            Box::pin(async {
+                Ok(Response {
+                    payload: None,
+                    rate_limit: None,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn archive_without_payload_is_an_error() {
+        let client = Client::from_api(Arc::new(MissingArchiveApi));
+        let image = Image::from_id(client, "abc-0").await.unwrap();
+
+        assert!(matches!(
+            image.get_archive().await,
+            Err(SmugMugError::ResponseMissing())
+        ));
+        assert!(matches!(
+            image.get_archive_stream().await,
+            Err(SmugMugError::ResponseMissing())
+        ));
+    }
+
+    #[tokio::test]
+    async fn resumable_download_requests_remainder_of_partial_file() {
+        let data = b"not really a jpeg".repeat(100);
+        let path = std::env::temp_dir().join(format!("smugmug-resume-{}.jpg", std::process::id()));
+        let part_path = path.with_extension("jpg.part");
+        std::fs::write(&part_path, &data[..500]).unwrap();
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: response_json(serde_json::json!({ "Image": image_value("abc", &data) })),
+            },
+            CannedResponse {
+                status: 206,
+                headers: vec![(
+                    "Content-Range",
+                    format!("bytes 500-{}/{}", data.len() - 1, data.len()),
+                )],
+                body: data[500..].to_vec(),
+            },
+        ]);
+        let client = get_transport_client(transport.clone()).build().unwrap();
+
+        let image = Image::from_id(client, "abc-0").await.unwrap();
+        let summary = image.download_archive_resumable(&path).await.unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        assert!(!part_path.exists());
+        assert_eq!(&summary.md5, image.archived_md5.as_ref().unwrap());
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[1].headers.get("Range").unwrap(), "bytes=500-");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resumable_download_of_complete_part_file_without_size_is_verified() {
+        let data = b"not really a jpeg".repeat(100);
+        let path =
+            std::env::temp_dir().join(format!("smugmug-complete-{}.jpg", std::process::id()));
+        let part_path = path.with_extension("jpg.part");
+        std::fs::write(&part_path, &data).unwrap();
+        let mut image_json = image_value("abc", &data);
+        image_json.as_object_mut().unwrap().remove("ArchivedSize");
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: response_json(serde_json::json!({ "Image": image_json })),
+            },
+            CannedResponse {
+                status: 416,
+                headers: vec![("Content-Range", format!("bytes */{}", data.len()))],
+                body: vec![],
+            },
+        ]);
+        let client = get_transport_client(transport.clone()).build().unwrap();
+
+        let image = Image::from_id(client, "abc-0").await.unwrap();
+        assert!(image.archived_size.is_none());
+        let summary = image.download_archive_resumable(&path).await.unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        assert!(!part_path.exists());
+        assert_eq!(summary.size, data.len() as u64);
+        assert_eq!(&summary.md5, image.archived_md5.as_ref().unwrap());
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(
+            requests[1].headers.get("Range").unwrap().to_str().unwrap(),
+            format!("bytes={}-", data.len())
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resumable_download_fails_verification_on_md5_mismatch() {
+        let data = b"not really a jpeg".repeat(100);
+        let path = std::env::temp_dir().join(format!("smugmug-verify-{}.jpg", std::process::id()));
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: response_json(serde_json::json!({ "Image": image_value("abc", &data) })),
+            },
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: b"something else entirely".repeat(74)[..data.len()].to_vec(),
+            },
+        ]);
+        let client = get_transport_client(transport).build().unwrap();
+
+        let image = Image::from_id(client, "abc-0").await.unwrap();
+        let result = image.download_archive_resumable(&path).await;
+
+        assert!(matches!(
+            result,
+            Err(SmugMugError::ArchiveVerificationFailed(..))
+        ));
+        assert!(!path.exists());
+        assert!(!path.with_extension("jpg.part").exists());
+    }
+
+    #[tokio::test]
+    async fn binary_downloads_participate_in_rate_limits() {
+        let reset = Utc::now().timestamp() + 600;
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![
+                    ("x-ratelimit-remaining", "7".to_string()),
+                    ("x-ratelimit-reset", reset.to_string()),
+                ],
+                body: b"image data".to_vec(),
+            },
+            CannedResponse {
+                status: 429,
+                headers: vec![("retry-after", "30".to_string())],
+                body: vec![],
+            },
+        ]);
+        let client = get_transport_client(transport).build().unwrap();
+        let url = "https://photos.smugmug.com/abc.jpg";
+
+        let resp = client.get_binary_data(url, None).await.unwrap();
+        assert_eq!(resp.payload.unwrap().as_ref(), b"image data");
+        assert_eq!(resp.rate_limit.unwrap().num_remaining_requests(), Some(7));
+        let rate_limit = client.get_last_rate_limit_window_update().unwrap();
+        assert_eq!(rate_limit.num_remaining_requests(), Some(7));
+
+        let result = client.get_binary_stream(url, None).await;
+        assert!(matches!(
+            result,
+            Err(SmugMugError::ApiResponseTooManyRequests(30))
+        ));
+    }
+
+    #[tokio::test]
+    async fn metrics_count_requests_retries_and_downloaded_bytes() {
+        let reset = Utc::now().timestamp() + 600;
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 429,
+                headers: vec![("retry-after", "0".to_string())],
+                body: vec![],
+            },
+            CannedResponse {
+                status: 200,
+                headers: vec![
+                    ("x-ratelimit-remaining", "5".to_string()),
+                    ("x-ratelimit-reset", reset.to_string()),
+                ],
+                body: user_json("apidemo"),
+            },
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: b"image data".to_vec(),
+            },
+        ]);
+        let client = get_transport_client(transport)
+            .retry_policy(RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+
+        User::from_id(client.clone(), "apidemo").await.unwrap();
+        client
+            .get_binary_data("https://photos.smugmug.com/abc.jpg", None)
+            .await
+            .unwrap();
+
+        let metrics = client.metrics();
+        let count = |endpoint: &str, status: &str| RequestCount {
+            method: "GET".to_string(),
+            endpoint: endpoint.to_string(),
+            status: status.to_string(),
+            count: 1,
+        };
+        assert_eq!(
+            metrics.requests,
+            [
+                count("/api/v2/user/{id}", "200"),
+                count("/api/v2/user/{id}", "429"),
+                count("photos.smugmug.com", "200"),
+            ]
+        );
+        assert_eq!(metrics.retries, 1);
+        assert_eq!(metrics.too_many_requests, 1);
+        assert_eq!(
+            metrics.bytes_downloaded,
+            (user_json("apidemo").len() + b"image data".len()) as u64
+        );
+        assert_eq!(metrics.num_remaining_requests, Some(5));
+        let user_latency = &metrics.latencies[0];
+        assert_eq!(user_latency.endpoint, "/api/v2/user/{id}");
+        assert_eq!(user_latency.count, 2);
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("# TYPE smugmug_requests_total counter\n"));
+        assert!(text.contains(
+            "smugmug_requests_total{method=\"GET\",endpoint=\"/api/v2/user/{id}\",status=\"429\"} 1\n"
+        ));
+        assert!(text.contains(
+            "smugmug_request_duration_seconds_count{method=\"GET\",endpoint=\"/api/v2/user/{id}\"} 2\n"
+        ));
+        assert!(text.contains("smugmug_retries_total 1\n"));
+        assert!(text.contains("smugmug_rate_limit_remaining 5\n"));
+    }
+
+    // Responds after a delay keeping track of the most requests in flight at once
+    #[derive(Default)]
+    struct SlowTransport {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl HttpTransport for SlowTransport {
+        fn send(&self, _req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+            Box::pin(async move {
+                let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(HttpResponse::from_bytes(
+                    http::StatusCode::OK,
+                    http::HeaderMap::new(),
+                    user_json("apidemo").into(),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrency_limits_requests_in_flight() {
+        let transport = Arc::new(SlowTransport::default());
+        let client = get_transport_client(transport.clone())
+            .max_concurrent_requests(2)
+            .max_concurrent_downloads(1)
+            .build()
+            .unwrap();
+
+        let users =
+            futures::future::try_join_all((0..6).map(|_| User::from_id(client.clone(), "apidemo")))
+                .await
+                .unwrap();
+        assert_eq!(users.len(), 6);
+        assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 2);
+
+        // The download slot is held until the body is dropped
+        let url = "https://photos.smugmug.com/abc.jpg";
+        let body = client.get_binary_stream(url, None).await.unwrap();
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(50),
+            client.get_binary_stream(url, None),
+        )
+        .await;
+        assert!(waiting.is_err());
+
+        // JSON requests have their own limit
+        User::from_id(client.clone(), "apidemo").await.unwrap();
+
+        drop(body);
+        client.get_binary_stream(url, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn http_cache_serves_cached_body_when_not_modified() {
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![("etag", "\"v1\"".to_string())],
+                body: user_json("apidemo"),
+            },
+            CannedResponse {
+                status: 304,
+                headers: vec![],
+                body: vec![],
+            },
+        ]);
+        let http_cache = Arc::new(InMemoryHttpCache::default());
+        let client = get_transport_client(transport.clone())
+            .http_cache(http_cache.clone())
+            .build()
+            .unwrap();
+
+        let user = User::from_id(client.clone(), "apidemo").await.unwrap();
+        assert_eq!(user.name, "apidemo");
+        assert_eq!(http_cache.len(), 1);
+
+        let user = User::from_id(client, "apidemo").await.unwrap();
+        assert_eq!(user.name, "apidemo");
+
+        let requests = transport.requests.lock().unwrap();
+        assert!(requests[0].headers.get("If-None-Match").is_none());
+        assert_eq!(requests[1].headers.get("If-None-Match").unwrap(), "\"v1\"");
+    }
+
+    #[tokio::test]
+    async fn object_cache_serves_repeat_reads_until_updated() {
+        let node_response = |name: &str| CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: response_json(serde_json::json!({ "Node": node_value("n1", name, "Folder") })),
+        };
+        let transport = MemoryTransport::new(vec![
+            node_response("before"),
+            node_response("after"),
+            node_response("after"),
+        ]);
+        let object_cache = Arc::new(ObjectCache::new(10));
+        let client = get_transport_client(transport.clone())
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .object_cache(object_cache.clone())
+            .build()
+            .unwrap();
+
+        let node = Node::from_id(client.clone(), "n1").await.unwrap();
+        let cached = Node::from_id(client.clone(), "n1").await.unwrap();
+        assert_eq!(cached.name, "before");
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+
+        node.update_node_data_with_client(client.clone(), br#"{"Name":"after"}"#.to_vec())
+            .await
+            .unwrap();
+        assert!(object_cache.is_empty());
+
+        let updated = Node::from_id(client, "n1").await.unwrap();
+        assert_eq!(updated.name, "after");
+        assert_eq!(transport.requests.lock().unwrap().len(), 3);
+    }
+
+    // Holds back PATCH requests until released so other requests can run while one is in flight
+    struct GatedTransport {
+        inner: Arc<MemoryTransport>,
+        gate: tokio::sync::Notify,
+    }
+
+    impl HttpTransport for GatedTransport {
+        fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+            Box::pin(async move {
+                if req.method == Method::PATCH {
+                    self.gate.notified().await;
+                }
+                self.inner.send(req).await
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn object_cache_drops_reads_made_during_an_update() {
+        let node_response = |name: &str| CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: response_json(serde_json::json!({ "Node": node_value("n1", name, "Folder") })),
+        };
+        let inner = MemoryTransport::new(vec![
+            node_response("before"),
+            node_response("after"),
+            node_response("after"),
+        ]);
+        let transport = Arc::new(GatedTransport {
+            inner: inner.clone(),
+            gate: tokio::sync::Notify::new(),
+        });
+        let client = get_transport_client(transport.clone())
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .object_cache(Arc::new(ObjectCache::new(10)))
+            .build()
+            .unwrap();
+        let url = "https://api.smugmug.com/api/v2/node/n1";
+
+        let (updated, read) = tokio::join!(
+            client.patch::<serde_json::Value>(url, br#"{"Name":"after"}"#.to_vec(), None),
+            async {
+                let read = Node::from_id(client.clone(), "n1").await;
+                transport.gate.notify_one();
+                read
+            }
+        );
+        updated.unwrap();
+        assert_eq!(read.unwrap().name, "before");
+
+        let node = Node::from_id(client, "n1").await.unwrap();
+        assert_eq!(node.name, "after");
+        assert_eq!(inner.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn journal_fetches_state_before_past_the_object_cache() {
+        let path = std::env::temp_dir().join(format!(
+            "smugmug-journal-cache-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let node_response = |name: &str| CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: response_json(serde_json::json!({ "Node": node_value("n1", name, "Folder") })),
+        };
+        let transport = MemoryTransport::new(vec![
+            node_response("cached"),
+            node_response("changed elsewhere"),
+            node_response("after"),
+        ]);
+        let journal = Arc::new(MutationJournal::open(&path).unwrap());
+        let client = get_transport_client(transport.clone())
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .object_cache(Arc::new(ObjectCache::new(10)))
+            .journal(journal.clone())
+            .build()
+            .unwrap();
+
+        let node = Node::from_id(client.clone(), "n1").await.unwrap();
+        node.update_node_data_with_client(client, br#"{"Name":"after"}"#.to_vec())
+            .await
+            .unwrap();
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(
+            entries[0].before.as_ref().unwrap()["Node"]["Name"],
+            "changed elsewhere"
+        );
+        assert_eq!(transport.requests.lock().unwrap().len(), 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    // Records whether the journal entry was already written when each PATCH was sent
+    struct JournalWatcher {
+        journal: Arc<MutationJournal>,
+        pending: Mutex<Vec<bool>>,
+    }
+
+    impl Interceptor for JournalWatcher {
+        fn on_request(&self, req: &mut HttpRequest) -> Result<(), SmugMugError> {
+            if req.method == Method::PATCH {
+                let entries = self.journal.entries()?;
+                self.pending
+                    .lock()
+                    .unwrap()
+                    .extend(entries.iter().map(|v| v.pending));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn journal_records_failed_mutation() {
+        let path = std::env::temp_dir().join(format!(
+            "smugmug-journal-failed-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let node_response = || CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: response_json(
+                serde_json::json!({ "Node": node_value("n1", "before", "Folder") }),
+            ),
+        };
+        let transport = MemoryTransport::new(vec![
+            node_response(),
+            node_response(),
+            CannedResponse {
+                status: 500,
+                headers: vec![],
+                body: vec![],
+            },
+        ]);
+        let journal = Arc::new(MutationJournal::open(&path).unwrap());
+        let watcher = Arc::new(JournalWatcher {
+            journal: journal.clone(),
+            pending: Mutex::new(vec![]),
+        });
+        let client = get_transport_client(transport.clone())
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .interceptor(watcher.clone())
+            .journal(journal.clone())
+            .build()
+            .unwrap();
+
+        let node = Node::from_id(client.clone(), "n1").await.unwrap();
+        let result = node
+            .update_node_data_with_client(client, br#"{"Name":"after"}"#.to_vec())
+            .await;
+        assert!(matches!(result, Err(SmugMugError::HttpStatus(500, _))));
+
+        // The entry was written before the PATCH went out and completed with the error after
+        assert_eq!(*watcher.pending.lock().unwrap(), vec![true]);
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].method, "PATCH");
+        assert_eq!(
+            entries[0].before.as_ref().unwrap()["Node"]["Name"],
+            "before"
+        );
+        assert!(entries[0].after.is_none());
+        assert!(entries[0].error.as_ref().unwrap().contains("500"));
+        assert!(!entries[0].pending);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn object_cache_skips_api_error_responses() {
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: serde_json::to_vec(&serde_json::json!({
+                    "Code": 404,
+                    "Message": "Not Found"
+                }))
+                .unwrap(),
+            },
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: response_json(
+                    serde_json::json!({ "Node": node_value("n1", "found", "Folder") }),
+                ),
+            },
+        ]);
+        let object_cache = Arc::new(ObjectCache::new(10));
+        let client = get_transport_client(transport.clone())
+            .object_cache(object_cache.clone())
+            .build()
+            .unwrap();
+
+        let missing = Node::from_id(client.clone(), "n1").await;
+        assert!(matches!(missing, Err(SmugMugError::ApiResponse(404, _))));
+        assert!(object_cache.is_empty());
+
+        let node = Node::from_id(client, "n1").await.unwrap();
+        assert_eq!(node.name, "found");
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn offline_mode_serves_disk_cache_snapshot() {
+        let dir = std::env::temp_dir().join(format!("smugmug-disk-cache-{}", std::process::id()));
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: user_json("apidemo"),
+        }]);
+        let creds = Creds::from_tokens("disk-cache-api-key", None, None, None);
+        let client = get_transport_client(transport.clone())
+            .creds(creds.clone())
+            .http_cache(Arc::new(DiskHttpCache::new(&dir)))
+            .build()
+            .unwrap();
+        User::from_id(client, "apidemo").await.unwrap();
+
+        // The API key sent in the query never makes it to disk
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            assert!(!path.to_string_lossy().contains("disk-cache-api-key"));
+            let contents = std::fs::read_to_string(&path).unwrap();
+            assert!(contents.contains("APIKey=REDACTED"));
+            assert!(!contents.contains("disk-cache-api-key"));
+        }
+
+        // A new cache over the same directory sees the snapshot
+        let offline_client = get_transport_client(transport.clone())
+            .creds(creds)
+            .http_cache(Arc::new(DiskHttpCache::new(&dir)))
+            .offline(true)
+            .build()
+            .unwrap();
+        let user = User::from_id(offline_client.clone(), "apidemo")
+            .await
+            .unwrap();
+        assert_eq!(user.name, "apidemo");
+
+        let result = User::from_id(offline_client.clone(), "someone-else").await;
+        assert!(matches!(result, Err(SmugMugError::OfflineCacheMiss(_))));
+        let url = "https://api.smugmug.com/api/v2/user/apidemo";
+        let result = offline_client
+            .patch::<serde_json::Value>(url, b"{}".to_vec(), None)
+            .await;
+        assert!(
+            matches!(result, Err(SmugMugError::OfflineUnsupported(method, _)) if method == "PATCH")
+        );
+        let result = offline_client.get_binary_data(url, None).await;
+        assert!(
+            matches!(result, Err(SmugMugError::OfflineUnsupported(method, _)) if method == "GET")
+        );
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn in_memory_http_cache_evicts_least_recently_used() {
+        let response = |etag: &str| CachedResponse {
+            etag: Some(etag.to_string()),
+            last_modified: None,
+            body: Bytes::from_static(b"{}"),
+        };
+        let http_cache = InMemoryHttpCache::new(2);
+        assert_eq!(http_cache.capacity(), 2);
+        http_cache.put("/api/v2/node/a", response("a"));
+        http_cache.put("/api/v2/node/b", response("b"));
+
+        // Reading a makes b the least recently used
+        assert!(http_cache.get("/api/v2/node/a").is_some());
+        http_cache.put("/api/v2/node/c", response("c"));
+
+        assert_eq!(http_cache.len(), 2);
+        assert!(http_cache.get("/api/v2/node/b").is_none());
+        assert_eq!(
+            http_cache.get("/api/v2/node/a").unwrap().etag.as_deref(),
+            Some("a")
+        );
+        assert!(http_cache.get("/api/v2/node/c").is_some());
+
+        // Replacing a cached url doesn't evict anything
+        http_cache.put("/api/v2/node/c", response("c2"));
+        assert_eq!(http_cache.len(), 2);
+        assert!(http_cache.get("/api/v2/node/a").is_some());
+    }
+
+    #[tokio::test]
+    async fn in_memory_http_cache_skips_responses_without_validators() {
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: user_json("apidemo"),
+        }]);
+        let http_cache = Arc::new(InMemoryHttpCache::default());
+        let client = get_transport_client(transport)
+            .http_cache(http_cache.clone())
+            .build()
+            .unwrap();
+
+        User::from_id(client, "apidemo").await.unwrap();
+        assert!(http_cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn cassette_replays_recorded_traffic_without_credentials() {
+        let path =
+            std::env::temp_dir().join(format!("smugmug-cassette-{}.jsonl", std::process::id()));
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 200,
+            headers: vec![("x-ratelimit-remaining", "42".to_string())],
+            body: user_json("apidemo"),
+        }]);
+        let creds = Creds::from_tokens(
+            "secret-api-key",
+            Some("secret"),
+            Some("token"),
+            Some("token_secret"),
+        );
+        let client = get_transport_client(transport)
+            .creds(creds.clone())
+            .cassette(&path, CassetteMode::Record)
+            .build()
+            .unwrap();
+        User::from_id(client, "apidemo").await.unwrap();
+
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert!(cassette.contains("/api/v2/user/apidemo"));
+        assert!(!cassette.contains("secret-api-key"));
+        assert!(!cassette.contains("OAuth"));
+
+        let client = Client::builder()
+            .creds(creds)
+            .cassette(&path, CassetteMode::Replay)
+            .build()
+            .unwrap();
+        let user = User::from_id(client.clone(), "apidemo").await.unwrap();
+        assert_eq!(user.name, "apidemo");
+
+        // Each recorded response is only served once
+        let result = User::from_id(client, "apidemo").await;
+        assert!(matches!(result, Err(SmugMugError::CassetteMismatch(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    struct ApiCall {
+        method: Method,
+        url: String,
+        data: Option<Vec<u8>>,
+    }
+
+    // Fake API serving canned objects and recording the requests made
+    #[derive(Default)]
+    struct FakeApi {
+        requests: Mutex<Vec<ApiCall>>,
+    }
+
+    impl SmugMugApi for FakeApi {
+        fn request<'a>(
+            &'a self,
+            method: Method,
+            url: &'a str,
+            data: Option<Vec<u8>>,
+            _params: Option<&'a ApiParams<'a>>,
+        ) -> BoxFuture<'a, Result<Response<serde_json::Value>, SmugMugError>> {
+            self.requests.lock().unwrap().push(ApiCall {
+                method,
+                url: url.to_string(),
+                data,
+            });
+            Box::pin(async move {
+                let payload = if url.contains("/image/") {
+                    serde_json::json!({ "Image": image_value("i1", b"fake image") })
+                } else {
+                    serde_json::json!({ "Node": node_value("n1", "fake", "Folder") })
+                };
+                Ok(Response {
+                    payload: Some(payload),
+                    rate_limit: None,
+                })
+            })
+        }
+
+        fn get_binary_stream<'a>(
+            &'a self,
+            _url: &'a str,
+            _params: Option<&'a ApiParams<'a>>,
+        ) -> BoxFuture<'a, Result<Response<HttpBodyStream>, SmugMugError>> {
+            Box::pin(async {
+                let body: HttpBodyStream = Box::pin(futures::stream::iter([Ok(
+                    Bytes::from_static(b"fake image"),
+                )]));
+                Ok(Response {
+                    payload: Some(body),
+                    rate_limit: None,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn objects_use_substituted_api() {
+        let api = Arc::new(FakeApi::default());
+        let client = Client::from_api(api.clone());
+
+        let node = Node::from_id(client.clone(), "n1").await.unwrap();
+        assert_eq!(node.name, "fake");
+        node.update_node_data_with_client(client.clone(), br#"{"Name":"renamed"}"#.to_vec())
+            .await
+            .unwrap();
+
+        let image = Image::from_id(client, "i1").await.unwrap();
+        let mut data = Vec::new();
+        let summary = image.download_archive_to(&mut data).await.unwrap();
+        assert_eq!(data, b"fake image");
+        assert_eq!(Some(&summary.md5), image.archived_md5.as_ref());
+
+        let requests = api.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].method, Method::PATCH);
+        assert!(requests[1].url.ends_with("/api/v2/node/n1"));
+        assert_eq!(
+            requests[1].data.as_deref(),
+            Some(&br#"{"Name":"renamed"}"#[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_children_stream_resumes_from_position() {
+        let next_page = "/api/v2/node/root!children?start=3&count=2";
+        let first_page = response_json(serde_json::json!({
+            "Node": [node_value("a1", "first", "Album"), node_value("a2", "second", "Album")],
+            "Pages": { "NextPage": next_page }
+        }));
+        let ok = |body: Vec<u8>| CannedResponse {
+            status: 200,
+            headers: vec![],
+            body,
+        };
+        let transport = MemoryTransport::new(vec![
+            ok(response_json(
+                serde_json::json!({ "Node": node_value("root", "root", "Folder") }),
+            )),
+            ok(first_page.clone()),
+            ok(first_page),
+            ok(response_json(serde_json::json!({
+                "Node": [node_value("a3", "third", "Album")],
+                "Pages": {}
+            }))),
+        ]);
+        let client = get_transport_client(transport.clone()).build().unwrap();
+        let root_node = Node::from_id(client, "root").await.unwrap();
+
+        let cancel = CancellationToken::new();
+        let mut children = root_node
+            .children_cancellable(
+                NodeTypeFilters::Any,
+                SortDirection::Ascending,
+                SortMethod::SortIndex,
+                cancel.clone(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(children.next().await.unwrap().unwrap().name, "first");
+        cancel.cancel();
+        assert!(children.next().await.is_none());
+
+        let position = children.resume_position().unwrap();
+        assert_eq!(position.offset, 1);
+        let first_url = transport.requests.lock().unwrap()[1].url.clone();
+        assert_eq!(
+            url::Url::parse(&position.url).unwrap().path(),
+            first_url.path()
+        );
+
+        let mut children = root_node
+            .children_cancellable(
+                NodeTypeFilters::Any,
+                SortDirection::Ascending,
+                SortMethod::SortIndex,
+                CancellationToken::new(),
+                Some(position),
+            )
+            .unwrap();
+        let mut names = Vec::new();
+        while let Some(child) = children.next().await {
+            names.push(child.unwrap().name);
+        }
+        assert_eq!(names, ["second", "third"]);
+        assert_eq!(children.resume_position(), None);
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[2].url.query(), first_url.query());
+        assert_eq!(
+            requests[3].url.query().unwrap(),
+            "start=3&count=2&_verbosity=1&APIKey=key"
+        );
+    }
+
+    // Sends the first chunk of the archive and then stalls after cancelling the token
+    struct StallingTransport {
+        image: Vec<u8>,
+        first_chunk: Bytes,
+        cancel: CancellationToken,
+    }
+
+    impl HttpTransport for StallingTransport {
+        fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+            Box::pin(async move {
+                if req.url.path().starts_with("/api/") {
+                    return Ok(HttpResponse::from_bytes(
+                        http::StatusCode::OK,
+                        http::HeaderMap::new(),
+                        self.image.clone().into(),
+                    ));
+                }
+                let cancel = self.cancel.clone();
+                let body = futures::stream::iter([Ok(self.first_chunk.clone())]).chain(
+                    futures::stream::once(async move {
+                        cancel.cancel();
+                        futures::future::pending().await
+                    }),
+                );
+                Ok(HttpResponse::new(
+                    http::StatusCode::OK,
+                    http::HeaderMap::new(),
+                    Box::pin(body),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_download_keeps_partial_file_for_resuming() {
+        let data = b"not really a jpeg".repeat(100);
+        let image_json = response_json(serde_json::json!({ "Image": image_value("abc", &data) }));
+        let path = std::env::temp_dir().join(format!("smugmug-cancel-{}.jpg", std::process::id()));
+        let part_path = path.with_extension("jpg.part");
+        let cancel = CancellationToken::new();
+        let transport = Arc::new(StallingTransport {
+            image: image_json.clone(),
+            first_chunk: Bytes::copy_from_slice(&data[..500]),
+            cancel: cancel.clone(),
+        });
+        let client = get_transport_client(transport).build().unwrap();
+
+        let image = Image::from_id(client, "abc-0").await.unwrap();
+        let outcome = image
+            .download_archive_resumable_cancellable(&path, &cancel)
+            .await
+            .unwrap();
+
+        let DownloadOutcome::Cancelled(partial) = outcome else {
+            panic!("Expected the download to be cancelled but got {outcome:?}");
+        };
+        assert_eq!(partial.size, 500);
+        assert_eq!(std::fs::read(&part_path).unwrap(), &data[..500]);
+        assert!(!path.exists());
+
+        // Calling again picks up from the offset reported
+        let transport = MemoryTransport::new(vec![
+            CannedResponse {
+                status: 200,
+                headers: vec![],
+                body: image_json,
+            },
+            CannedResponse {
+                status: 206,
+                headers: vec![(
+                    "Content-Range",
+                    format!("bytes 500-{}/{}", data.len() - 1, data.len()),
+                )],
+                body: data[500..].to_vec(),
+            },
+        ]);
+        let client = get_transport_client(transport.clone()).build().unwrap();
+        let image = Image::from_id(client, "abc-0").await.unwrap();
+        let outcome = image
+            .download_archive_resumable_cancellable(&path, &CancellationToken::new())
+            .await
+            .unwrap();
+
+        assert!(!outcome.is_cancelled());
+        assert_eq!(outcome.into_summary().size, data.len() as u64);
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        assert!(!part_path.exists());
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[1].headers.get("Range").unwrap(), "bytes=500-");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn budget_fails_fast_once_exhausted() {
+        let transport = MemoryTransport::new(
+            (0..4)
+                .map(|_| CannedResponse {
+                    status: 200,
+                    headers: vec![],
+                    body: user_json("apidemo"),
+                })
+                .collect(),
+        );
+        let client = get_transport_client(transport.clone()).build().unwrap();
+
+        let scoped = client.with_budget(2);
+        User::from_id(scoped.clone(), "apidemo").await.unwrap();
+        User::from_id(scoped.clone(), "apidemo").await.unwrap();
+        let result = User::from_id(scoped.clone(), "apidemo").await;
+        assert!(matches!(result, Err(SmugMugError::BudgetExhausted(2))));
+
+        let budget = scoped.budget().unwrap();
+        assert_eq!(budget.consumed(), 2);
+        assert_eq!(budget.rejected(), 1);
+        assert!(budget.is_exhausted());
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+
+        // The client the scope was made from isn't limited
+        assert!(client.budget().is_none());
+        User::from_id(client.clone(), "apidemo").await.unwrap();
+
+        // A nested scope also draws from the outer one
+        let outer = client.with_budget(1);
+        let inner = outer.with_budget(5);
+        User::from_id(inner.clone(), "apidemo").await.unwrap();
+        let result = User::from_id(inner.clone(), "apidemo").await;
+        assert!(matches!(result, Err(SmugMugError::BudgetExhausted(1))));
+        assert_eq!(inner.budget().unwrap().consumed(), 1);
+        assert_eq!(inner.budget().unwrap().remaining(), 4);
+        assert_eq!(outer.budget().unwrap().consumed(), 1);
+        assert_eq!(transport.requests.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn budget_counts_only_calls_sent_in_dry_run() {
+        let transport = MemoryTransport::new(vec![CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: response_json(serde_json::json!({ "Node": node_value("n1", "root", "Folder") })),
+        }]);
+        let client = get_transport_client(transport.clone())
+            .dry_run(true)
+            .build()
+            .unwrap()
+            .with_budget(10);
+        let url = "https://api.smugmug.com/api/v2/node/n1";
+
+        // A PATCH fetches the current object while a POST or DELETE sends nothing
+        client
+            .patch::<serde_json::Value>(url, br#"{"Name":"new"}"#.to_vec(), None)
+            .await
+            .unwrap();
+        client
+            .post::<serde_json::Value>(url, br#"{"Name":"child"}"#.to_vec(), None)
+            .await
+            .unwrap();
+        client.delete::<serde_json::Value>(url, None).await.unwrap();
+
+        assert_eq!(client.budget().unwrap().consumed(), 1);
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn budget_counts_journaled_state_before() {
+        let path = std::env::temp_dir().join(format!(
+            "smugmug-journal-budget-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let node_response = || CannedResponse {
+            status: 200,
+            headers: vec![],
+            body: response_json(serde_json::json!({ "Node": node_value("n1", "root", "Folder") })),
+        };
+        let transport = MemoryTransport::new(vec![node_response(), node_response()]);
+        let client = get_transport_client(transport.clone())
+            .creds(Creds::from_tokens(
+                "key",
+                Some("secret"),
+                Some("token"),
+                Some("token_secret"),
+            ))
+            .journal(Arc::new(MutationJournal::open(&path).unwrap()))
+            .build()
+            .unwrap()
+            .with_budget(1);
+        let url = "https://api.smugmug.com/api/v2/node/n1";
+
+        // The GET of the state before uses up the budget so the PATCH itself is refused
+        let result = client
+            .patch::<serde_json::Value>(url, br#"{"Name":"new"}"#.to_vec(), None)
+            .await;
+        assert!(matches!(result, Err(SmugMugError::BudgetExhausted(1))));
+        let budget = client.budget().unwrap();
+        assert_eq!(budget.consumed(), 1);
+        assert_eq!(budget.rejected(), 1);
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::GET);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn registry_runs_operation_across_accounts() {
+        let reset = (Utc::now().timestamp() + 600).to_string();
+        let account = |remaining: &str| {
+            MemoryTransport::new(vec![CannedResponse {
+                status: 200,
+                headers: vec![
+                    ("x-ratelimit-remaining", remaining.to_string()),
+                    ("x-ratelimit-reset", reset.clone()),
+                ],
+                body: user_json(&format!("user{remaining}")),
+            }])
+        };
+        let north = account("10");
+        let south = account("20");
+        let broken = MemoryTransport::new(vec![CannedResponse {
+            status: 500,
+            headers: vec![],
+            body: vec![],
+        }]);
+
+        let mut accounts = AccountRegistry::new();
+        for (name, transport) in [("north", &north), ("south", &south), ("broken", &broken)] {
+            let transport: Arc<dyn HttpTransport> = transport.clone();
+            accounts.add_client(name, get_transport_client(transport).build().unwrap());
+        }
+        assert_eq!(
+            accounts.names().collect::<Vec<_>>(),
+            ["broken", "north", "south"]
+        );
+
+        let users = accounts.run_all(User::authenticated_user_info).await;
+
+        assert!(!users.all_succeeded());
+        let names: Vec<(&str, &str)> = users
+            .successes()
+            .map(|(account, user)| (account, user.name.as_str()))
+            .collect();
+        assert_eq!(names, [("north", "user10"), ("south", "user20")]);
+        let errors: Vec<&str> = users.errors().map(|(account, _)| account).collect();
+        assert_eq!(errors, ["broken"]);
+        assert!(matches!(
+            users.get("broken"),
+            Some(Err(SmugMugError::HttpStatus(500, _)))
+        ));
+
+        // Each account keeps its own rate limit window
+        for (name, remaining) in [("north", 10), ("south", 20)] {
+            let window = accounts
+                .client(name)
+                .unwrap()
+                .get_last_rate_limit_window_update()
+                .unwrap();
+            assert_eq!(window.num_remaining_requests(), Some(remaining));
+        }
+        for transport in [&north, &south, &broken] {
+            assert_eq!(transport.requests.lock().unwrap().len(), 1);
+        }
+    }
+}
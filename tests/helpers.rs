@@ -6,12 +6,17 @@
  *  at your option.
  */
 use dotenvy::dotenv;
+use futures::future::BoxFuture;
+use http::{HeaderMap, StatusCode};
 use serde::Deserialize;
-use smugmug::v2::Client;
+use smugmug::v2::{
+    Client, ClientBuilder, Creds, HttpRequest, HttpResponse, HttpTransport, SmugMugError,
+};
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::PathBuf;
-use std::sync::Once;
+use std::sync::{Arc, Mutex, Once};
 
 #[allow(dead_code)]
 static LOGGER_INIT: Once = Once::new();
@@ -80,3 +85,129 @@ pub(crate) fn get_read_only_client() -> Client {
     let creds = get_read_only_auth_tokens().unwrap();
     Client::new(creds)
 }
+
+// Response served by the in-memory transport
+#[allow(dead_code)]
+pub(crate) struct CannedResponse {
+    pub(crate) status: u16,
+    pub(crate) headers: Vec<(&'static str, String)>,
+    pub(crate) body: Vec<u8>,
+}
+
+// Transport serving canned responses in order and recording the requests it was sent
+#[allow(dead_code)]
+#[derive(Default)]
+pub(crate) struct MemoryTransport {
+    pub(crate) responses: Mutex<VecDeque<CannedResponse>>,
+    pub(crate) requests: Mutex<Vec<HttpRequest>>,
+}
+
+#[allow(dead_code)]
+impl MemoryTransport {
+    pub(crate) fn new(responses: Vec<CannedResponse>) -> Arc<Self> {
+        Arc::new(Self {
+            responses: Mutex::new(responses.into()),
+            ..Default::default()
+        })
+    }
+}
+
+impl HttpTransport for MemoryTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SmugMugError>> {
+        self.requests.lock().unwrap().push(req);
+        let canned = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("No more canned responses");
+        Box::pin(async move {
+            let mut headers = HeaderMap::new();
+            for (name, value) in canned.headers {
+                headers.insert(name, value.parse().unwrap());
+            }
+            Ok(HttpResponse::from_bytes(
+                StatusCode::from_u16(canned.status).unwrap(),
+                headers,
+                canned.body.into(),
+            ))
+        })
+    }
+}
+
+// Creates a client that sends requests to the given transport
+#[allow(dead_code)]
+pub(crate) fn get_transport_client(transport: Arc<dyn HttpTransport>) -> ClientBuilder {
+    Client::builder()
+        .creds(Creds::from_tokens("key", None, None, None))
+        .transport(transport)
+}
+
+// JSON body returned for a User request
+#[allow(dead_code)]
+pub(crate) fn user_json(name: &str) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "Code": 200,
+        "Message": "Ok",
+        "Response": {
+            "User": {
+                "Uri": format!("/api/v2/user/{name}"),
+                "Name": name,
+                "WebUri": format!("https://{name}.smugmug.com"),
+                "Uris": { "Node": "/api/v2/node/root" }
+            }
+        }
+    }))
+    .unwrap()
+}
+
+// JSON for a Node object
+#[allow(dead_code)]
+pub(crate) fn node_value(id: &str, name: &str, node_type: &str) -> serde_json::Value {
+    serde_json::json!({
+        "Uri": format!("/api/v2/node/{id}"),
+        "Name": name,
+        "UrlName": name,
+        "WebUri": format!("https://apidemo.smugmug.com/{name}"),
+        "HasChildren": node_type == "Folder",
+        "IsRoot": false,
+        "NodeID": id,
+        "Type": node_type,
+        "DateAdded": "2025-01-01T00:00:00Z",
+        "DateModified": "2025-01-02T00:00:00Z",
+        "Uris": { "ChildNodes": format!("/api/v2/node/{id}!children") }
+    })
+}
+
+// JSON for an Image object whose archive holds the given data
+#[allow(dead_code)]
+pub(crate) fn image_value(key: &str, data: &[u8]) -> serde_json::Value {
+    serde_json::json!({
+        "Uri": format!("/api/v2/image/{key}-0"),
+        "Title": key,
+        "Altitude": 0,
+        "Format": "JPG",
+        "FileName": format!("{key}.jpg"),
+        "ImageKey": key,
+        "KeywordArray": [],
+        "ArchivedUri": format!("https://photos.smugmug.com/{key}.jpg"),
+        "ArchivedMD5": format!("{:x}", md5::compute(data)),
+        "ArchivedSize": data.len(),
+        "Processing": false,
+        "IsVideo": false,
+        "Hidden": false,
+        "DateTimeUploaded": "2025-01-01T00:00:00Z",
+        "LastUpdated": "2025-01-02T00:00:00Z"
+    })
+}
+
+// JSON body returned for the given response
+#[allow(dead_code)]
+pub(crate) fn response_json(response: serde_json::Value) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "Code": 200,
+        "Message": "Ok",
+        "Response": response,
+    }))
+    .unwrap()
+}
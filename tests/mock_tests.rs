@@ -0,0 +1,297 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+#![cfg(feature = "mock")]
+
+#[cfg(test)]
+mod test {
+    use futures::TryStreamExt;
+    use serde_json::json;
+    use smugmug::v2::mock::{MockLibrary, MockServer};
+    use smugmug::v2::{
+        Album, CreateAlbumProps, Image, MutationJournal, Node, NodeTypeFilters, RetryPolicy,
+        SmugMugError, SortDirection, SortMethod, User,
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    // Library with a folder holding an album of three images
+    fn library() -> (MockLibrary, String) {
+        let mut library = MockLibrary::new("apidemo").with_page_size(2);
+        let root = library.root_node_id().to_string();
+        let folder = library.add_folder(&root, "Travel");
+        library.add_folder(&root, "Family");
+        let album_key = library.add_album(&folder, "Iceland 2024");
+        for i in 0..3 {
+            library.add_image(&album_key, &format!("img{i}.jpg"), vec![i; 100]);
+        }
+        (library, album_key)
+    }
+
+    async fn root_node(server: &MockServer) -> Node {
+        let client = server.client_builder().build().unwrap();
+        User::authenticated_user_info(client)
+            .await
+            .unwrap()
+            .node()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn node_children_and_album_images_are_paged() {
+        let (library, _) = library();
+        let server = MockServer::start(library).await.unwrap();
+        let root = root_node(&server).await;
+        assert!(root.is_root);
+
+        let children: Vec<Node> = root
+            .children(
+                NodeTypeFilters::Any,
+                SortDirection::Ascending,
+                SortMethod::SortIndex,
+            )
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        let names: Vec<&str> = children.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["Travel", "Family"]);
+
+        let albums: Vec<Node> = children[0]
+            .children(
+                NodeTypeFilters::Album,
+                SortDirection::Ascending,
+                SortMethod::SortIndex,
+            )
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        let album = albums[0].album().await.unwrap();
+        assert_eq!(album.name, "Iceland 2024");
+        assert_eq!(album.image_count, 3);
+
+        // Three images with a page size of two takes two pages
+        let requests_before = server.request_count();
+        let images: Vec<Image> = album.images().unwrap().try_collect().await.unwrap();
+        assert_eq!(images.len(), 3);
+        assert_eq!(server.request_count() - requests_before, 2);
+
+        let summary = images[2]
+            .download_archive_to(&mut Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(summary.size, 100);
+        assert_eq!(Some(&summary.md5), images[2].archived_md5.as_ref());
+    }
+
+    #[tokio::test]
+    async fn resumed_download_of_complete_part_file_is_not_downloaded_again() {
+        let (library, album_key) = library();
+        let server = MockServer::start(library).await.unwrap();
+        let client = server.client_builder().build().unwrap();
+        let album = Album::from_id(client, &album_key).await.unwrap();
+        let mut images: Vec<Image> = album.images().unwrap().try_collect().await.unwrap();
+        let mut image = images.remove(1);
+        // Without the size the server has to report the file is already complete
+        image.archived_size = None;
+
+        let path =
+            std::env::temp_dir().join(format!("smugmug-mock-resume-{}.jpg", std::process::id()));
+        let part_path = path.with_extension("jpg.part");
+        std::fs::write(&part_path, vec![1u8; 100]).unwrap();
+
+        let requests_before = server.request_count();
+        let summary = image.download_archive_resumable(&path).await.unwrap();
+
+        assert_eq!(server.request_count() - requests_before, 1);
+        assert_eq!(summary.size, 100);
+        assert_eq!(Some(&summary.md5), image.archived_md5.as_ref());
+        assert_eq!(std::fs::read(&path).unwrap(), vec![1u8; 100]);
+        assert!(!part_path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn album_created_and_updated() {
+        let (library, album_key) = library();
+        let server = MockServer::start(library).await.unwrap();
+        let root = root_node(&server).await;
+
+        let album = root
+            .create_album(CreateAlbumProps {
+                name: "New Album".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(album.name, "New Album");
+        let root_id = server.library().root_node_id().to_string();
+        assert_eq!(server.library().child_node_ids(&root_id).len(), 3);
+
+        let album = album.set_upload_key("secret").await.unwrap();
+        assert_eq!(album.upload_key.as_deref(), Some("secret"));
+
+        let client = server.client_builder().build().unwrap();
+        let albums = Album::from_id_slice(client, &[&album_key, &album.album_key])
+            .await
+            .unwrap();
+        assert_eq!(albums.len(), 2);
+        assert_eq!(
+            server.library().album_fields(&album.album_key).unwrap()["UploadKey"],
+            "secret"
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_rehearses_updates_without_sending_them() {
+        let (library, album_key) = library();
+        let server = MockServer::start(library).await.unwrap();
+        let client = server.client_builder().dry_run(true).build().unwrap();
+        assert!(client.is_dry_run());
+
+        let album = Album::from_id(client.clone(), &album_key).await.unwrap();
+        let updated = album.set_upload_key("rehearsal").await.unwrap();
+        assert_eq!(updated.upload_key.as_deref(), Some("rehearsal"));
+        assert_eq!(updated.name, "Iceland 2024");
+        let fields = server.library().album_fields(&album_key).cloned().unwrap();
+        assert!(fields.get("UploadKey").is_none());
+
+        let root = User::authenticated_user_info(client)
+            .await
+            .unwrap()
+            .node()
+            .await
+            .unwrap();
+        let requests_before = server.request_count();
+        let created = root
+            .create_album(CreateAlbumProps {
+                name: "Rehearsal".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.name, "Rehearsal");
+        assert_eq!(server.request_count(), requests_before);
+        let root_id = server.library().root_node_id().to_string();
+        assert_eq!(server.library().child_node_ids(&root_id).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn journaled_mutations_are_undone() {
+        let path =
+            std::env::temp_dir().join(format!("smugmug-journal-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (library, album_key) = library();
+        let server = MockServer::start(library).await.unwrap();
+        let journal = Arc::new(MutationJournal::open(&path).unwrap());
+        let client = server
+            .client_builder()
+            .journal(journal.clone())
+            .build()
+            .unwrap();
+
+        let album = Album::from_id(client.clone(), &album_key).await.unwrap();
+        album.set_upload_key("secret").await.unwrap();
+        let root = User::authenticated_user_info(client.clone())
+            .await
+            .unwrap()
+            .node()
+            .await
+            .unwrap();
+        root.create_album(CreateAlbumProps {
+            name: "Temporary".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].method, "PATCH");
+        assert_eq!(
+            entries[0].request_body,
+            Some(json!({ "UploadKey": "secret" }))
+        );
+        assert_eq!(
+            entries[0].before.as_ref().unwrap()["Album"]["Name"],
+            "Iceland 2024"
+        );
+        assert_eq!(
+            entries[0].after.as_ref().unwrap()["Album"]["UploadKey"],
+            "secret"
+        );
+        assert_eq!(entries[1].method, "POST");
+        assert!(entries[1].before.is_none());
+
+        // Undo creating the album then setting the upload key
+        assert_eq!(client.undo(&entries).await.unwrap(), 2);
+        let root_id = server.library().root_node_id().to_string();
+        assert_eq!(server.library().child_node_ids(&root_id).len(), 2);
+        let album = Album::from_id(client.clone(), &album_key).await.unwrap();
+        assert_eq!(album.upload_key, None);
+
+        // The undo is journaled as well and deletions can't be undone
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[2].method, "DELETE");
+        assert!(matches!(
+            client.undo(&entries[2..3]).await,
+            Err(SmugMugError::JournalUndo(2, _))
+        ));
+
+        // Failing partway reports the mutations already reverted
+        album.set_upload_key("again").await.unwrap();
+        let mut entries = journal.entries().unwrap();
+        entries[1].after = Some(json!({ "Album": { "Uri": "/api/v2/album/missing" } }));
+        let result = client.undo(&[entries[1].clone(), entries[4].clone()]).await;
+        assert!(matches!(result, Err(SmugMugError::UndoIncomplete(1, _))));
+        let album = Album::from_id(client.clone(), &album_key).await.unwrap();
+        assert_eq!(album.upload_key, None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rate_limits_reported_and_429_retried() {
+        let (library, _) = library();
+        let server = MockServer::start(library.with_rate_limit(100))
+            .await
+            .unwrap();
+        server.fail_next_with_too_many_requests(1, 0);
+        let client = server
+            .client_builder()
+            .retry_policy(RetryPolicy::new(2).with_initial_backoff(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+
+        let user = User::authenticated_user_info(client.clone()).await.unwrap();
+        assert_eq!(user.name, "apidemo");
+        assert_eq!(server.request_count(), 2);
+        let rate_limit = client.get_last_rate_limit_window_update().unwrap();
+        assert_eq!(rate_limit.num_remaining_requests(), Some(99));
+
+        // Without retries the 429 is returned along with when to retry
+        server.fail_next_with_too_many_requests(1, 30);
+        let client = server.client_builder().build().unwrap();
+        let result = User::authenticated_user_info(client).await;
+        assert!(matches!(
+            result,
+            Err(SmugMugError::ApiResponseTooManyRequests(30))
+        ));
+    }
+
+    #[tokio::test]
+    async fn unknown_objects_are_not_found() {
+        let (library, _) = library();
+        let server = MockServer::start(library).await.unwrap();
+        let client = server.client_builder().build().unwrap();
+
+        let result = Node::from_id(client, "missing").await;
+        assert!(matches!(result, Err(SmugMugError::HttpStatus(404, _))));
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Utc};
+    use smugmug::v2::{
+        FileRateLimitStore, InMemoryRateLimitStore, RateLimitStore, RateLimitWindow,
+    };
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn window(remaining: u64, timestamp: DateTime<Utc>) -> Arc<RateLimitWindow> {
+        Arc::new(
+            serde_json::from_value(serde_json::json!({
+                "num_remaining_requests": remaining,
+                "current_window_reset_datetime": null,
+                "retry_after_seconds": null,
+                "timestamp": timestamp,
+            }))
+            .unwrap(),
+        )
+    }
+
+    fn store_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "smugmug-rate-limit-{name}-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn in_memory_store_keeps_newest_window() {
+        let store = InMemoryRateLimitStore::default();
+        assert!(store.load().is_none());
+
+        let now = Utc::now();
+        store.store(window(10, now));
+        assert_eq!(store.load().unwrap().num_remaining_requests(), Some(10));
+
+        // An older window doesn't replace a newer one
+        store.store(window(50, now - chrono::Duration::seconds(5)));
+        assert_eq!(store.load().unwrap().num_remaining_requests(), Some(10));
+
+        store.store(window(9, now + chrono::Duration::seconds(1)));
+        assert_eq!(store.load().unwrap().num_remaining_requests(), Some(9));
+    }
+
+    #[test]
+    fn file_store_window_is_loaded_by_another_store() {
+        let path = store_path("shared");
+        let now = Utc::now();
+        let writer = FileRateLimitStore::new(&path);
+        writer.store(window(10, now));
+
+        let reader = FileRateLimitStore::new(&path);
+        let loaded = reader.load().unwrap();
+        assert_eq!(loaded.num_remaining_requests(), Some(10));
+        assert_eq!(loaded.timestamp(), now);
+
+        // An older window from another process doesn't replace the newer one
+        FileRateLimitStore::new(&path).store(window(50, now - chrono::Duration::seconds(5)));
+        let reader = FileRateLimitStore::new(&path);
+        assert_eq!(reader.load().unwrap().num_remaining_requests(), Some(10));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn file_store_writes_and_refreshes_in_the_background() {
+        let path = store_path("background");
+        let now = Utc::now();
+        let writer = FileRateLimitStore::new(&path);
+        writer.store(window(10, now));
+        // Served from memory without waiting on the file
+        assert_eq!(writer.load().unwrap().num_remaining_requests(), Some(10));
+
+        let reader = FileRateLimitStore::new(&path).with_refresh_interval(Duration::ZERO);
+        let loaded = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(window) = reader.load() {
+                    return window;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("The window wasn't read from the file");
+        assert_eq!(loaded.num_remaining_requests(), Some(10));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) 2025 Craig Hamilton and Contributors.
+ * Licensed under either of
+ *  - Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0> OR
+ *  - MIT license <http://opensource.org/licenses/MIT>
+ *  at your option.
+ */
+#![cfg(feature = "tracing")]
+
+mod helpers;
+
+#[cfg(test)]
+mod test {
+    use crate::helpers::{
+        CannedResponse, MemoryTransport, get_transport_client, node_value, response_json, user_json,
+    };
+    use futures::TryStreamExt;
+    use serde_json::json;
+    use smugmug::v2::{Node, NodeTypeFilters, SortDirection, SortMethod, User};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    #[derive(Debug, Clone)]
+    struct RecordedSpan {
+        name: &'static str,
+        parent: Option<u64>,
+        fields: HashMap<String, String>,
+    }
+
+    impl RecordedSpan {
+        fn field(&self, name: &str) -> Option<&str> {
+            self.fields.get(name).map(|v| v.as_str())
+        }
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    // Subscriber recording the spans and their fields
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        spans: Arc<Mutex<Vec<RecordedSpan>>>,
+        entered: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl SpanRecorder {
+        fn spans(&self, name: &str) -> Vec<RecordedSpan> {
+            let spans = self.spans.lock().unwrap();
+            spans.iter().filter(|v| v.name == name).cloned().collect()
+        }
+
+        fn span(&self, id: u64) -> RecordedSpan {
+            self.spans.lock().unwrap()[id as usize - 1].clone()
+        }
+    }
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            let parent = match attrs.parent() {
+                Some(parent) => Some(parent.into_u64()),
+                None if attrs.is_contextual() => self.entered.lock().unwrap().last().copied(),
+                None => None,
+            };
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(RecordedSpan {
+                name: attrs.metadata().name(),
+                parent,
+                fields,
+            });
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            let span = &mut spans[span.into_u64() as usize - 1];
+            values.record(&mut FieldVisitor(&mut span.fields));
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.entered.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _span: &Id) {
+            self.entered.lock().unwrap().pop();
+        }
+    }
+
+    fn ok(body: Vec<u8>) -> CannedResponse {
+        CannedResponse {
+            status: 200,
+            headers: vec![("x-ratelimit-remaining", "42".to_string())],
+            body,
+        }
+    }
+
+    #[tokio::test]
+    async fn request_span_records_endpoint_status_and_rate_limit() {
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        let transport = MemoryTransport::new(vec![ok(user_json("apidemo"))]);
+        let client = get_transport_client(transport).build().unwrap();
+
+        User::from_id(client, "apidemo").await.unwrap();
+
+        let spans = recorder.spans("smugmug.request");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].field("method"), Some("GET"));
+        assert_eq!(spans[0].field("endpoint"), Some("/api/v2/user/{id}"));
+        assert_eq!(spans[0].field("status"), Some("200"));
+        assert_eq!(spans[0].field("rate_limit_remaining"), Some("42"));
+        assert!(spans[0].field("latency_ms").is_some());
+
+        // The API key is never part of the span
+        let url = spans[0].field("url").unwrap();
+        assert!(url.contains("APIKey=REDACTED"));
+        assert!(!url.contains("APIKey=key"));
+    }
+
+    #[tokio::test]
+    async fn stream_requests_are_within_numbered_page_spans() {
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        let transport = MemoryTransport::new(vec![
+            ok(response_json(
+                json!({ "Node": node_value("root", "root", "Folder") }),
+            )),
+            ok(response_json(json!({
+                "Node": [node_value("a1", "first", "Album")],
+                "Pages": { "NextPage": "/api/v2/node/root!children?start=2&count=1" }
+            }))),
+            ok(response_json(json!({
+                "Node": [node_value("a2", "second", "Album")],
+                "Pages": {}
+            }))),
+        ]);
+        let client = get_transport_client(transport).build().unwrap();
+
+        let root_node = Node::from_id(client, "root").await.unwrap();
+        let children: Vec<Node> = root_node
+            .children(
+                NodeTypeFilters::Album,
+                SortDirection::Ascending,
+                SortMethod::Name,
+            )
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(children.len(), 2);
+
+        let spans = recorder.spans("smugmug.request");
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].parent, None);
+        for (span, page) in spans[1..].iter().zip(["1", "2"]) {
+            assert_eq!(span.field("endpoint"), Some("/api/v2/node/{id}!children"));
+            let parent = recorder.span(span.parent.unwrap());
+            assert_eq!(parent.name, "smugmug.page");
+            assert_eq!(parent.field("page"), Some(page));
+        }
+    }
+}